@@ -2,6 +2,7 @@ import { blake2bHex } from 'blakejs';
 import { RollupClient, Signer } from './rollup';
 import { Tweet } from './tweet';
 import { TezosToolkit } from '@taquito/taquito';
+import { encodeKeyHash } from '@taquito/utils';
 import { SmartRollupAddMessagesOperation } from '@taquito/taquito/dist/types/operations/smart-rollup-add-messages-operation';
 import { COMMITMENT_INTERVAL, BLOCK_TIME, CEMENTED_PERIOD } from '../config';
 
@@ -83,22 +84,21 @@ class Tzwitter {
   async getTweet(tweetId: number): Promise<Tweet> {
     const publicKeyHash = await this.signer.publicKeyHash();
 
-    const authorPath = `/tweets/${tweetId}/author`;
-    const contentPath = `/tweets/${tweetId}/content`;
+    const recordPath = `/tweets/${tweetId}/record`;
     const likesPath = `/tweets/${tweetId}/likes`;
     const isLikedPath = `/accounts/${publicKeyHash}/likes/${tweetId}`;
     const collectedBlockPath = `/tweets/${tweetId}/collected_hash`;
 
-    const authorBytes = await this.rollupClient.getState(authorPath);
-    const contentBytes = await this.rollupClient.getState(contentPath);
+    const recordBytes = await this.rollupClient.getState(recordPath);
     const likesBytes = await this.rollupClient.getState(likesPath);
     const isLiked = await this.rollupClient.getState(isLikedPath);
     const collectedBlockBytes = await this.rollupClient.getState(
       collectedBlockPath,
     );
 
-    const author = Buffer.from(authorBytes, 'hex').toString('utf-8');
-    const content = Buffer.from(contentBytes, 'hex').toString('utf-8');
+    // The record is the author (1 byte tag + 20 bytes hash) followed by the content
+    const author = encodeKeyHash(recordBytes.slice(0, 42));
+    const content = Buffer.from(recordBytes.slice(42), 'hex').toString('utf-8');
     const likes = Number('0x' + likesBytes);
 
     // Let's estimate a mint date
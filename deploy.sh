@@ -16,11 +16,17 @@ which wasm-strip > /dev/null || (echo "wasm-strip should be installed" && echo "
 # ligo
 which ligo > /dev/null || (echo "wasm-strip should be installed" && echo "https://ligolang.org/docs/intro/installation?lang=jsligo" exit 1)
 
+# The admin of the rollup and of the layer 1 contract is the account deploying them
+ADMIN=$(octez-client show address $account_alias | grep "Hash:" | awk '{print $2}')
+
 # deploying the layer 1 contract
 MICHELSON=$(ligo compile contract smart_contract/dummy-fa2.jsligo)
-STORAGE=$(ligo compile storage smart_contract/dummy-fa2.jsligo initial_storage)
+STORAGE=$(ligo compile storage smart_contract/dummy-fa2.jsligo "{...initial_storage, admin: (\"$ADMIN\" as address)}")
+
+L1_CONTRACT=$(octez-client originate contract tzwitter transferring 0 from $account_alias running "$MICHELSON" --init "$STORAGE" --burn-cap 1.0 --force | grep "New contract" | awk '{ print $3}')
 
-export TZWITTER_L1_CONTRACT=$(octez-client originate contract tzwitter transferring 0 from $account_alias running "$MICHELSON" --init "$STORAGE" --burn-cap 1.0 --force | grep "New contract" | awk '{ print $3}')
+# The ticketer whose tickets can be deposited on the rollup
+if [[ "$TZWITTER_TICKETER" == "" ]]; then echo "TZWITTER_TICKETER should be set to the address of the ticketer" && exit 1;fi
 
 # Compiling the kernel
 cargo build --release --target wasm32-unknown-unknown --manifest-path kernel/Cargo.toml
@@ -35,10 +41,35 @@ cp kernel/target/wasm32-unknown-unknown/release/tzwitter_kernel.wasm ./rollup/ke
 # Installing the kernel
 wasm-strip ./rollup/kernel.wasm
 
+# The configuration written in the durable storage at install time
+# - the layer 1 contract receiving the collected tweets
+# - the admin, which is the account deploying the rollup
+# - the whitelisted ticketer
+# - the governance contract, if TZWITTER_GOVERNANCE is set
+cat > rollup/setup.yaml <<EOF
+instructions:
+  - set:
+      value: $(printf "%s" "$L1_CONTRACT" | xxd -p -c 256)
+      to: /config/l1_contract
+  - set:
+      value: $(printf "%s" "$ADMIN" | xxd -p -c 256)
+      to: /config/admin
+  - set:
+      value: 00
+      to: /config/ticketers/$TZWITTER_TICKETER
+EOF
+if [[ "$TZWITTER_GOVERNANCE" != "" ]]; then
+cat >> rollup/setup.yaml <<EOF
+  - set:
+      value: $(printf "%s" "$TZWITTER_GOVERNANCE" | xxd -p -c 256)
+      to: /config/governance
+EOF
+fi
+
 # Using the smart-rollup-installer
 # It will generate the installer.hex
 # And split the kernel
-smart-rollup-installer get-reveal-installer --upgrade-to rollup/kernel.wasm --output rollup/installer.hex --preimages-dir rollup/wasm_2_0_0
+smart-rollup-installer get-reveal-installer --upgrade-to rollup/kernel.wasm --output rollup/installer.hex --preimages-dir rollup/wasm_2_0_0 --setup-file rollup/setup.yaml
 
 # Setup the DAC
 mkdir -p rollup/wasm_2_0_0
@@ -50,13 +81,20 @@ cp kernel/target/wasm32-unknown-unknown/release/tzwitter_kernel.wasm ./rollup/ke
 # Save the bytes of the kernel as a variable
 KERNEL_INSTALLER=$(cat rollup/installer.hex)
 
-# Originate the kernel 
+# Originate the kernel
+# The rollup receives:
+# - on the left of the left, the admin operations of the governance contract: (bytes %governance)
+# - on the right of the left, the tickets of the whitelisted ticketers: (pair (address %receiver) (ticket unit))
+# - on the right, the tweets sent back by the layer 1 contract: (pair (nat %id) (address %receiver))
 SOR_ADDR=$(octez-client originate smart rollup from $account_alias \
   of kind wasm_2_0_0 \
-  of type bytes \
+  of type "or (or bytes (pair address (ticket unit))) (pair nat address)" \
   with kernel "${KERNEL_INSTALLER}" \
   --burn-cap 999 | grep "Address:" | awk '{print $2}')
 
+# The layer 1 contract only sends the tweets back to this rollup
+octez-client transfer 0 from $account_alias to $L1_CONTRACT --entrypoint setRollup --arg "\"$SOR_ADDR\"" --burn-cap 1.0
+
 # # Setting up the rollup data directory
 octez-smart-rollup-node-alpha init operator config for "${SOR_ADDR}" with operators "$account_alias" --data-dir rollup
 
@@ -1,8 +1,58 @@
 pub const MAGIC_BYTE: u8 = 0x74;
 
-#[cfg(not(debug_assertions))]
-pub const L1_TOKEN_CONTRACT_ADDRESS: &'static str = env!("TZWITTER_L1_CONTRACT");
-#[cfg(debug_assertions)]
-pub const L1_TOKEN_CONTRACT_ADDRESS: &'static str = "KT1RycYvM4EVs6BAXWEsGXaAaRqiMP53KT4w";
+/// Prefix of the external messages signed by the admin
+pub const ADMIN_MAGIC_BYTE: u8 = 0x61;
 
+/// Default entrypoint of the layer 1 token contract
+/// It can be overridden by the value stored under /config/l1_entrypoint
 pub const L1_TOKEN_CONTRACT_ENTRYPOINT: &'static str = "mint";
+
+/// Default number of levels a receipt is kept in the durable state
+/// It can be overridden by the value stored under /config/receipt_retention
+/// 40_320 levels represent two weeks with 30 seconds blocks
+pub const RECEIPT_RETENTION: u64 = 40_320;
+
+/// Maximum number of receipts deleted per level
+/// So that the pruning never exceeds the tick budget of a level
+pub const MAX_PRUNED_RECEIPTS_PER_LEVEL: u64 = 100;
+
+/// Number of levels after which a collected tweet is considered withdrawn to layer 1
+/// It is the time for the commitment of the collect to be published and cemented
+/// 40 levels for the commitment period and 40 levels for the cementation
+pub const COLLECT_FINALITY_DELAY: u32 = 80;
+
+/// Maximum number of auctions ending at the same level
+/// So that the settlement never exceeds the tick budget of a level
+pub const MAX_AUCTIONS_PER_LEVEL: u64 = 100;
+
+/// Maximum number of levels between the opening and the end of an auction
+/// So that the tweet and the escrowed bids are not locked forever
+/// 40_320 levels represent two weeks with 30 seconds blocks
+pub const MAX_AUCTION_DURATION: u32 = 40_320;
+
+/// Maximum percentage of a sale paid to the author of the tweet
+pub const MAX_ROYALTIES: u64 = 25;
+
+/// Maximum size of a kernel installed by an upgrade, 4MB
+pub const MAX_KERNEL_SIZE: usize = 4 * 1024 * 1024;
+
+/// Maximum number of pages of a kernel installed by an upgrade
+/// MAX_KERNEL_SIZE bytes fill 1_026 content pages of 4KB, indexed by 10 pages of hashes
+pub const MAX_KERNEL_PAGES: u64 = 1_040;
+
+/// Maximum number of pages of a new kernel revealed during a run
+/// So that the installation never exceeds the tick budget of a run, it resumes after a reboot
+pub const MAX_KERNEL_PAGES_PER_RUN: u64 = 500;
+
+/// Maximum number of media attached to a tweet
+pub const MAX_MEDIA_PER_TWEET: usize = 4;
+
+/// Maximum size of the MIME type of a media
+pub const MAX_MIME_TYPE_SIZE: usize = 127;
+
+/// Maximum size of the body of a long tweet, 32KB
+pub const MAX_LONG_TWEET_SIZE: usize = 32 * 1024;
+
+/// Maximum depth of the tree of preimages revealed by the kernel
+/// 3 levels are enough for more than 60MB of content
+pub const MAX_DAC_LEVELS: usize = 3;
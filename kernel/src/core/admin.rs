@@ -0,0 +1,96 @@
+use crate::core::hash::Blake2b;
+use crate::core::nonce::Nonce;
+use crate::core::public_key::PublicKey;
+use crate::core::public_key_hash::PublicKeyHash;
+use crate::core::signature::Signature;
+use serde::Deserialize;
+
+/// Layer 1 contract receiving the collected tweets
+#[derive(Deserialize)]
+pub struct SetL1Contract {
+    pub address: String,
+    pub entrypoint: String,
+}
+
+/// An operation reserved to the admin key and to the governance contract
+#[derive(Deserialize)]
+pub enum AdminContent {
+    SetL1Contract(SetL1Contract),
+    SetReceiptRetention(u64),
+    Pause,
+    Unpause,
+    AddModerator(PublicKeyHash),
+    RemoveModerator(PublicKeyHash),
+    /// Root preimage hash of the new kernel, encoded in hexadecimal
+    UpgradeKernel(String),
+}
+
+/// The nonces of the admin are independent from the nonces of the accounts
+#[derive(Deserialize)]
+pub struct AdminInner {
+    nonce: Nonce,
+    pub content: AdminContent,
+}
+
+impl AdminInner {
+    /// Returns the nonce of the inner
+    pub fn nonce(&self) -> &Nonce {
+        &self.nonce
+    }
+
+    /// Hash of the message, that the admin should sign
+    ///
+    /// It is prefixed by "admin", so that it cannot be mistaken for the hash of a user message
+    pub fn hash(&self) -> Blake2b {
+        let AdminInner { nonce, content } = &self;
+        let nonce = nonce.to_string();
+        let string = match content {
+            AdminContent::SetL1Contract(SetL1Contract {
+                address,
+                entrypoint,
+            }) => format!("admin{}set_l1_contract{}{}", nonce, address, entrypoint),
+            AdminContent::SetReceiptRetention(retention) => {
+                format!("admin{}set_receipt_retention{}", nonce, retention)
+            }
+            AdminContent::Pause => format!("admin{}pause", nonce),
+            AdminContent::Unpause => format!("admin{}unpause", nonce),
+            AdminContent::AddModerator(moderator) => {
+                format!("admin{}add_moderator{}", nonce, moderator.to_string())
+            }
+            AdminContent::RemoveModerator(moderator) => {
+                format!("admin{}remove_moderator{}", nonce, moderator.to_string())
+            }
+            AdminContent::UpgradeKernel(root_hash) => {
+                format!("admin{}upgrade_kernel{}", nonce, root_hash)
+            }
+        };
+        Blake2b::from(string.as_bytes())
+    }
+}
+
+/// A message signed by the admin of the rollup
+///
+/// It is sent as an external message prefixed by the ADMIN_MAGIC_BYTE
+#[derive(Deserialize)]
+pub struct AdminMessage {
+    pkey: PublicKey,
+    signature: Signature,
+    pub inner: AdminInner,
+}
+
+impl AdminMessage {
+    /// Returns the public key of the message
+    pub fn public_key(&self) -> &PublicKey {
+        &self.pkey
+    }
+
+    /// Returns the signature of the message
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Returns the hash of the message
+    pub fn hash(&self) -> Blake2b {
+        self.inner.hash()
+    }
+}
@@ -0,0 +1,21 @@
+use crate::core::public_key_hash::PublicKeyHash;
+use tezos_rollup_encoding::michelson::ticket::TicketHash;
+
+/// A tweet sent back to the rollup by the layer 1 token contract
+///
+/// The token has been burnt on layer 1,
+/// So the tweet can be unfrozen and given to the receiver
+pub struct TweetDeposit {
+    pub tweet_id: u64,
+    pub receiver: PublicKeyHash,
+}
+
+/// Tickets sent to the rollup by a whitelisted ticketer
+///
+/// The amount is credited to the balance of the receiver
+pub struct TicketDeposit {
+    pub ticket: TicketHash,
+    pub ticketer: String,
+    pub amount: u64,
+    pub receiver: PublicKeyHash,
+}
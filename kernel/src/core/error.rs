@@ -1,3 +1,6 @@
+use crate::core::hash::Blake2b;
+use crate::core::message::InboxPosition;
+
 /// Rperesents the error of the read_input functions
 #[derive(Debug)]
 pub enum ReadInputError {
@@ -5,10 +8,11 @@ pub enum ReadInputError {
     NotATzwitterMessage,
     /// There is no more messages
     EndOfInbox,
-    /// There is an error in the bytes to string deserialization
-    FromUtf8Error(std::string::FromUtf8Error),
-    /// There is an error in the string to Message deserialization
-    SerdeJson(serde_json_wasm::de::Error),
+    /// The message is addressed to this rollup but cannot be decoded
+    ///
+    /// It holds the hash of the raw external payload (magic byte included),
+    /// So that a receipt can be stored for this message
+    Malformed(InboxPosition, Blake2b, Error),
     /// There is an error runtime
     Runtime(host::runtime::RuntimeError),
 }
@@ -33,6 +37,40 @@ pub enum Error {
     BinError(tezos_data_encoding::enc::BinError),
     EntrypointError(tezos_rollup_encoding::entrypoint::EntrypointError),
     NotInfoPerLevelMsg,
+    SerdeJson(serde_json_wasm::de::Error),
+    TransferOfFrozenTweet,
+    LikeOfFrozenTweet,
+    DeletionOfFrozenTweet,
+    TweetNotCollected,
+    UnsupportedReceiver,
+    TicketHashError(tezos_rollup_encoding::michelson::ticket::TicketHashError),
+    UnknownTicketer,
+    BalanceOverflow,
+    InsufficientBalance,
+    TicketNotFound,
+    InvalidAmount,
+    InvalidDestination,
+    ListingOfFrozenTweet,
+    NotListed,
+    PriceMismatch,
+    TweetInAuction,
+    AuctionNotFound,
+    AuctionEnded,
+    BidTooLow,
+    InvalidEndLevel,
+    TooManyAuctions,
+    AuctionTooLong,
+    RoyaltiesTooHigh,
+    NotAdmin,
+    L1ContractNotConfigured,
+    Paused,
+    InvalidPreimageHash,
+    InvalidKernel,
+    LongTweetTooLarge,
+    InvalidLongTweet,
+    TooManyMedia,
+    InvalidMedia,
+    NotModerator,
 }
 
 impl ToString for Error {
@@ -54,11 +92,106 @@ impl ToString for Error {
             Error::BinError(_) => "Cannot serialize michelson to binary",
             Error::EntrypointError(_) => "Not a correct entrypoint",
             Error::NotInfoPerLevelMsg => "Was waiting for the InfoPerLevel message",
+            Error::SerdeJson(_) => "Cannot deserialize the message",
+            Error::TransferOfFrozenTweet => "A collected tweet cannot be transferred",
+            Error::LikeOfFrozenTweet => "A collected tweet cannot be liked",
+            Error::DeletionOfFrozenTweet => "A collected tweet cannot be deleted",
+            Error::TweetNotCollected => "The tweet has not been collected",
+            Error::UnsupportedReceiver => "The receiver should be a tz1 address",
+            Error::TicketHashError(_) => "Cannot compute the hash of the ticket",
+            Error::UnknownTicketer => "The ticketer is not whitelisted",
+            Error::BalanceOverflow => "The balance cannot exceed the maximum amount",
+            Error::InsufficientBalance => "The balance is lower than the amount",
+            Error::TicketNotFound => "Ticket not found",
+            Error::InvalidAmount => "The amount should be positive",
+            Error::InvalidDestination => "The destination should be a KT1 address",
+            Error::ListingOfFrozenTweet => "A collected tweet cannot be put on sale",
+            Error::NotListed => "The tweet is not on sale",
+            Error::PriceMismatch => "The price of the tweet has changed",
+            Error::TweetInAuction => "The tweet is auctioned",
+            Error::AuctionNotFound => "The tweet is not auctioned",
+            Error::AuctionEnded => "The auction has ended",
+            Error::BidTooLow => "The bid should be greater than the reserve and the best bid",
+            Error::InvalidEndLevel => "The end level of the auction has passed",
+            Error::TooManyAuctions => "Too many auctions end at this level",
+            Error::AuctionTooLong => "The auction exceeds the maximum duration",
+            Error::RoyaltiesTooHigh => "The royalties exceed the maximum percentage",
+            Error::NotAdmin => "The message is not signed by the admin",
+            Error::L1ContractNotConfigured => "The layer 1 contract is not configured",
+            Error::Paused => "The rollup is paused",
+            Error::InvalidPreimageHash => "The preimage hash is invalid",
+            Error::InvalidKernel => "The revealed kernel is invalid",
+            Error::LongTweetTooLarge => "The body of the long tweet is too large",
+            Error::InvalidLongTweet => "The body of the long tweet cannot be revealed",
+            Error::TooManyMedia => "Too many media are attached to the tweet",
+            Error::InvalidMedia => "The media is invalid",
+            Error::NotModerator => "The message is not signed by a moderator",
         };
         err.to_string()
     }
 }
 
+impl Error {
+    /// Returns a stable numeric code of the error
+    ///
+    /// The code is stored in the receipts, so that clients can know why an operation failed.
+    /// A code must never be reused for another error.
+    pub fn code(&self) -> u16 {
+        match self {
+            Error::FromUtf8Error(_) => 1,
+            Error::Runtime(_) => 2,
+            Error::Ed25519Compact(_) => 3,
+            Error::InvalidSignature => 4,
+            Error::InvalidNonce => 5,
+            Error::PathError(_) => 6,
+            Error::StateDeserializarion => 7,
+            Error::TweetNotFound => 8,
+            Error::TweetAlreadyLiked => 9,
+            Error::NotOwner => 10,
+            Error::TweetAlreadyCollected => 11,
+            Error::FromBase58CheckError => 12,
+            Error::BigIntError => 13,
+            Error::BinError(_) => 14,
+            Error::EntrypointError(_) => 15,
+            Error::NotInfoPerLevelMsg => 16,
+            Error::SerdeJson(_) => 17,
+            Error::TransferOfFrozenTweet => 18,
+            Error::LikeOfFrozenTweet => 19,
+            Error::DeletionOfFrozenTweet => 21,
+            Error::TweetNotCollected => 23,
+            Error::UnsupportedReceiver => 24,
+            Error::TicketHashError(_) => 25,
+            Error::UnknownTicketer => 26,
+            Error::BalanceOverflow => 27,
+            Error::InsufficientBalance => 28,
+            Error::TicketNotFound => 29,
+            Error::InvalidAmount => 30,
+            Error::InvalidDestination => 31,
+            Error::ListingOfFrozenTweet => 32,
+            Error::NotListed => 33,
+            Error::PriceMismatch => 34,
+            Error::TweetInAuction => 35,
+            Error::AuctionNotFound => 36,
+            Error::AuctionEnded => 37,
+            Error::BidTooLow => 38,
+            Error::InvalidEndLevel => 39,
+            Error::TooManyAuctions => 40,
+            Error::RoyaltiesTooHigh => 41,
+            Error::NotAdmin => 42,
+            Error::L1ContractNotConfigured => 43,
+            Error::Paused => 44,
+            Error::InvalidPreimageHash => 45,
+            Error::InvalidKernel => 46,
+            Error::LongTweetTooLarge => 47,
+            Error::InvalidLongTweet => 48,
+            Error::TooManyMedia => 50,
+            Error::InvalidMedia => 51,
+            Error::NotModerator => 52,
+            Error::AuctionTooLong => 53,
+        }
+    }
+}
+
 macro_rules! register_error {
     ($name:ident, $error:ty) => {
         impl From<$error> for Error {
@@ -74,9 +207,14 @@ register_error!(Ed25519Compact, ed25519_compact::Error);
 register_error!(PathError, host::path::PathError);
 register_error!(Runtime, host::runtime::RuntimeError);
 register_error!(BinError, tezos_data_encoding::enc::BinError);
+register_error!(SerdeJson, serde_json_wasm::de::Error);
 register_error!(
     EntrypointError,
     tezos_rollup_encoding::entrypoint::EntrypointError
 );
+register_error!(
+    TicketHashError,
+    tezos_rollup_encoding::michelson::ticket::TicketHashError
+);
 
 pub type Result<A> = std::result::Result<A, Error>;
@@ -1,5 +1,6 @@
 macro_rules! define_blake2b {
     ($name:ident, $size:expr) => {
+        #[derive(Debug)]
         pub struct $name {
             inner: [u8; $size],
         }
@@ -0,0 +1,7 @@
+/// Information about the inbox level being processed
+pub struct LevelInfo {
+    /// The current level
+    pub level: u32,
+    /// The hash of the previous block
+    pub previous_hash: String,
+}
@@ -0,0 +1,27 @@
+use crate::core::public_key_hash::PublicKeyHash;
+use tezos_rollup_encoding::michelson::ticket::TicketHash;
+
+/// A tweet put on sale by its owner
+///
+/// The price is an amount of the given ticket
+pub struct Listing {
+    pub ticket: TicketHash,
+    pub price: u64,
+}
+
+/// The highest bid of an auction, its amount is escrowed
+pub struct BestBid {
+    pub bidder: PublicKeyHash,
+    pub amount: u64,
+}
+
+/// A tweet auctioned by its owner until the end level
+///
+/// The bids are amounts of the given ticket, and must be greater than the reserve
+pub struct Auction {
+    pub seller: PublicKeyHash,
+    pub ticket: TicketHash,
+    pub reserve: u64,
+    pub end_level: u32,
+    pub best_bid: Option<BestBid>,
+}
@@ -0,0 +1,103 @@
+use crate::constants::MAX_MIME_TYPE_SIZE;
+use crate::core::error::*;
+use host::rollup_core::PREIMAGE_HASH_SIZE;
+use serde::Deserialize;
+
+/// Alphabet of the base58 encoding of the CIDv0
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Size of a CIDv0, which is the base58 encoding of a sha256 multihash
+const CID_V0_SIZE: usize = 46;
+
+/// Maximum size of a CIDv1 encoded in base32
+const MAX_CID_V1_SIZE: usize = 128;
+
+/// An attachment of a tweet
+///
+/// The content is stored off-chain, the hash is either the root preimage hash of its DAC pages,
+/// Encoded in hexadecimal, or an IPFS CID
+#[derive(Deserialize)]
+pub struct Media {
+    pub mime_type: String,
+    pub size: u64,
+    pub hash: String,
+}
+
+impl Media {
+    /// Check the descriptor of the attachment
+    pub fn validate(&self) -> Result<()> {
+        let is_valid =
+            is_mime_type(&self.mime_type) && self.size > 0 && is_content_hash(&self.hash);
+        match is_valid {
+            true => Ok(()),
+            false => Err(Error::InvalidMedia),
+        }
+    }
+}
+
+/// A MIME type is of the form type/subtype, restricted to the characters allowed by RFC 6838
+fn is_mime_type(mime_type: &str) -> bool {
+    let is_name = |name: &str| {
+        !name.is_empty()
+            && name
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || b"!#$&-^_.+".contains(&byte))
+    };
+    match mime_type.split_once('/') {
+        Some((kind, subtype)) => {
+            mime_type.len() <= MAX_MIME_TYPE_SIZE && is_name(kind) && is_name(subtype)
+        }
+        None => false,
+    }
+}
+
+/// The hash is a preimage hash in hexadecimal, a CIDv0 in base58 or a CIDv1 in base32
+fn is_content_hash(hash: &str) -> bool {
+    let is_preimage_hash = hash.len() == 2 * PREIMAGE_HASH_SIZE
+        && hash.bytes().all(|byte| byte.is_ascii_hexdigit());
+    let is_cid_v0 = hash.len() == CID_V0_SIZE
+        && hash.starts_with("Qm")
+        && hash.bytes().all(|byte| BASE58_ALPHABET.contains(&byte));
+    let is_cid_v1 = match hash.strip_prefix('b') {
+        Some(encoded) => {
+            !encoded.is_empty()
+                && hash.len() <= MAX_CID_V1_SIZE
+                && encoded
+                    .bytes()
+                    .all(|byte| matches!(byte, b'a'..=b'z' | b'2'..=b'7'))
+        }
+        None => false,
+    };
+    is_preimage_hash || is_cid_v0 || is_cid_v1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Media;
+
+    fn media(mime_type: &str, size: u64, hash: &str) -> Media {
+        Media {
+            mime_type: mime_type.to_string(),
+            size,
+            hash: hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_media() {
+        let preimage_hash = "007d218ff04dbbada13a87dadd1686fffa3ac617be7804e1503e4b0d94cea5a1d5";
+        let cid_v0 = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+        let cid_v1 = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+        assert!(media("image/png", 1024, preimage_hash).validate().is_ok());
+        assert!(media("image/jpeg", 1024, cid_v0).validate().is_ok());
+        assert!(media("video/mp4", 1024, cid_v1).validate().is_ok());
+
+        assert!(media("image", 1024, cid_v0).validate().is_err());
+        assert!(media("image/", 1024, cid_v0).validate().is_err());
+        assert!(media("image/png png", 1024, cid_v0).validate().is_err());
+        assert!(media("image/png", 0, cid_v0).validate().is_err());
+        assert!(media("image/png", 1024, "QmNotAHash").validate().is_err());
+        assert!(media("image/png", 1024, &preimage_hash[2..]).validate().is_err());
+        assert!(media("image/png", 1024, "bAFY").validate().is_err());
+    }
+}
@@ -1,4 +1,7 @@
+use crate::core::admin::{AdminContent, AdminMessage};
+use crate::core::deposit::{TicketDeposit, TweetDeposit};
 use crate::core::hash::Blake2b;
+use crate::core::media::Media;
 use crate::core::nonce::Nonce;
 use crate::core::public_key::PublicKey;
 use crate::core::public_key_hash::PublicKeyHash;
@@ -9,6 +12,25 @@ use serde::Deserialize;
 pub struct PostTweet {
     pub author: PublicKeyHash, // define a new type for public key
     pub content: String,
+    /// Percentage of the secondary sales paid to the author
+    #[serde(default)]
+    pub royalties: u64,
+    #[serde(default)]
+    pub media: Vec<Media>,
+}
+
+/// A tweet whose body is too large for an inbox message
+///
+/// The body is the root preimage hash of its pages, encoded in hexadecimal
+#[derive(Deserialize)]
+pub struct PostLongTweet {
+    pub author: PublicKeyHash,
+    pub body: String,
+    /// Percentage of the secondary sales paid to the author
+    #[serde(default)]
+    pub royalties: u64,
+    #[serde(default)]
+    pub media: Vec<Media>,
 }
 
 #[derive(Deserialize)]
@@ -17,12 +39,66 @@ pub struct Transfer {
     pub destination: PublicKeyHash,
 }
 
+/// Withdraw tickets to a layer 1 contract
+///
+/// The ticket is identified by its hash, encoded in hexadecimal
+#[derive(Deserialize)]
+pub struct Withdraw {
+    pub ticket: String,
+    pub amount: u64,
+    pub destination: String,
+    pub entrypoint: String,
+}
+
+/// Tip the author of a tweet with tickets of the balance
+#[derive(Deserialize)]
+pub struct Tip {
+    pub tweet_id: u64,
+    pub ticket: String,
+    pub amount: u64,
+}
+
+/// Put a tweet on sale, or buy it at the given price
+///
+/// The buyer signs the price, so that they never pay more than expected
+#[derive(Deserialize)]
+pub struct Sale {
+    pub tweet_id: u64,
+    pub ticket: String,
+    pub price: u64,
+}
+
+/// Auction a tweet until the end level, included
+#[derive(Deserialize)]
+pub struct OpenAuction {
+    pub tweet_id: u64,
+    pub ticket: String,
+    pub reserve: u64,
+    pub end_level: u32,
+}
+
+/// Bid on an auctioned tweet, the amount is in the ticket of the auction
+#[derive(Deserialize)]
+pub struct Bid {
+    pub tweet_id: u64,
+    pub amount: u64,
+}
+
 #[derive(Deserialize)]
 pub enum Content {
     PostTweet(PostTweet),
+    PostLongTweet(PostLongTweet),
     LikeTweet(u64),
     Transfer(Transfer),
     Collect(u64),
+    Withdraw(Withdraw),
+    Tip(Tip),
+    ListTweet(Sale),
+    CancelListing(u64),
+    BuyTweet(Sale),
+    OpenAuction(OpenAuction),
+    Bid(Bid),
+    DeleteTweet(u64),
 }
 
 #[derive(Deserialize)]
@@ -38,6 +114,27 @@ impl Inner {
     }
 }
 
+/// Position of a message in the inbox
+#[derive(Debug, Clone, Copy)]
+pub struct InboxPosition {
+    pub level: u32,
+    pub index: u32,
+}
+
+/// An input of the inbox that is addressed to the kernel
+pub enum Input {
+    /// A signed message sent by a user
+    Message(Message),
+    /// A message signed by the admin of the rollup
+    AdminMessage(AdminMessage),
+    /// An operation sent by the governance contract, identified by the hash of the inbox message
+    Governance(Blake2b, AdminContent),
+    /// A tweet sent back by the layer 1 token contract, identified by the hash of the inbox message
+    TweetDeposit(Blake2b, TweetDeposit),
+    /// Tickets sent by a whitelisted ticketer, identified by the hash of the inbox message
+    TicketDeposit(Blake2b, TicketDeposit),
+}
+
 #[derive(Deserialize)]
 pub struct Message {
     pkey: PublicKey,
@@ -61,12 +158,24 @@ impl Message {
         &self.inner
     }
 
-    /// Returns the hash of the message
+    /// Returns the hash of the message, under which its receipt is stored
+    ///
+    /// The public key is hashed with the inner,
+    /// So that two accounts signing the same inner do not share a receipt
     pub fn hash(&self) -> Blake2b {
-        self.inner.hash()
+        let string = format!("{}:{}", self.pkey.to_b58(), self.inner.hash().to_string());
+        Blake2b::from(string.as_bytes())
     }
 }
 
+/// The media are hashed one after the other, as "{mime_type} {size} {hash};"
+fn media_to_string(media: &[Media]) -> String {
+    media
+        .iter()
+        .map(|media| format!("{} {} {};", media.mime_type, media.size, media.hash))
+        .collect()
+}
+
 impl Inner {
     /// Hash of the message
     /// This hash is what the client should signed
@@ -74,10 +183,53 @@ impl Inner {
         // The nonce, and content should be hashed
         let Inner { nonce, content } = &self;
         match &content {
-            Content::PostTweet(PostTweet { author, content }) => {
+            // The royalties and the media are only hashed when they are set,
+            // So that former clients still work
+            // They are placed before the author, which always starts with "tz"
+            Content::PostTweet(PostTweet {
+                author,
+                content,
+                royalties: 0,
+                media,
+            }) if media.is_empty() => {
                 let string = format!("{}{}{}", nonce.to_string(), author.to_string(), content);
                 Blake2b::from(string.as_bytes())
             }
+            // The fields are separated by a colon, so that the royalties cannot run into the media
+            // The content is the last field, it can contain a colon
+            Content::PostTweet(PostTweet {
+                author,
+                content,
+                royalties,
+                media,
+            }) => {
+                let string = format!(
+                    "{}royalties:{}:{}:{}:{}",
+                    nonce.to_string(),
+                    royalties,
+                    media_to_string(media),
+                    author.to_string(),
+                    content
+                );
+                Blake2b::from(string.as_bytes())
+            }
+            // The fields are separated by a colon, like the ones of a tweet with royalties or media
+            Content::PostLongTweet(PostLongTweet {
+                author,
+                body,
+                royalties,
+                media,
+            }) => {
+                let string = format!(
+                    "{}long_tweet:{}:{}:{}:{}",
+                    nonce.to_string(),
+                    royalties,
+                    media_to_string(media),
+                    author.to_string(),
+                    body
+                );
+                Blake2b::from(string.as_bytes())
+            }
             Content::LikeTweet(tweet_id) => {
                 let string = format!("{}{}", nonce.to_string(), tweet_id);
                 Blake2b::from(string.as_bytes())
@@ -95,6 +247,77 @@ impl Inner {
                 let string = format!("{}{}", nonce.to_string(), tweet_id);
                 Blake2b::from(string.as_bytes())
             }
+            // The name of the operation is hashed, so that a signature cannot be reused for another operation
+            Content::Withdraw(Withdraw {
+                ticket,
+                amount,
+                destination,
+                entrypoint,
+            }) => {
+                let string = format!(
+                    "{}withdraw{}{}{}{}",
+                    nonce.to_string(),
+                    ticket,
+                    amount,
+                    destination,
+                    entrypoint
+                );
+                Blake2b::from(string.as_bytes())
+            }
+            // The fields are separated by a colon, which cannot appear in the numbers around it
+            // So that two different operations cannot have the same hash
+            Content::Tip(Tip {
+                tweet_id,
+                ticket,
+                amount,
+            }) => {
+                let string = format!("{}tip:{}:{}:{}", nonce.to_string(), tweet_id, ticket, amount);
+                Blake2b::from(string.as_bytes())
+            }
+            Content::ListTweet(Sale {
+                tweet_id,
+                ticket,
+                price,
+            }) => {
+                let string = format!("{}list:{}:{}:{}", nonce.to_string(), tweet_id, ticket, price);
+                Blake2b::from(string.as_bytes())
+            }
+            Content::CancelListing(tweet_id) => {
+                let string = format!("{}cancel:{}", nonce.to_string(), tweet_id);
+                Blake2b::from(string.as_bytes())
+            }
+            Content::BuyTweet(Sale {
+                tweet_id,
+                ticket,
+                price,
+            }) => {
+                let string = format!("{}buy:{}:{}:{}", nonce.to_string(), tweet_id, ticket, price);
+                Blake2b::from(string.as_bytes())
+            }
+            Content::OpenAuction(OpenAuction {
+                tweet_id,
+                ticket,
+                reserve,
+                end_level,
+            }) => {
+                let string = format!(
+                    "{}auction:{}:{}:{}:{}",
+                    nonce.to_string(),
+                    tweet_id,
+                    ticket,
+                    reserve,
+                    end_level
+                );
+                Blake2b::from(string.as_bytes())
+            }
+            Content::Bid(Bid { tweet_id, amount }) => {
+                let string = format!("{}bid:{}:{}", nonce.to_string(), tweet_id, amount);
+                Blake2b::from(string.as_bytes())
+            }
+            Content::DeleteTweet(tweet_id) => {
+                let string = format!("{}delete:{}", nonce.to_string(), tweet_id);
+                Blake2b::from(string.as_bytes())
+            }
         }
     }
 }
@@ -103,8 +326,11 @@ impl Inner {
 mod tests {
     use std::num::ParseIntError;
 
-    use super::{Content, Inner, PostTweet};
-    use crate::core::{message::Message, nonce::Nonce, public_key::PublicKey};
+    use super::{Content, Inner, PostTweet, Tip};
+    use crate::core::{
+        media::Media, message::Message, nonce::Nonce, public_key::PublicKey,
+        public_key_hash::PublicKeyHash,
+    };
 
     #[test]
     fn test_hash() {
@@ -118,6 +344,8 @@ mod tests {
             content: Content::PostTweet(PostTweet {
                 author,
                 content: "Hello world".to_string(),
+                royalties: 0,
+                media: vec![],
             }),
         };
 
@@ -125,6 +353,46 @@ mod tests {
         assert_eq!(expected, hash.to_string());
     }
 
+    #[test]
+    fn test_tip_hash_is_unambiguous() {
+        // Without separators, both tips would be hashed as 12000...0055
+        let tip = |tweet_id, ticket: String, amount| Inner {
+            nonce: Nonce::default(),
+            content: Content::Tip(Tip {
+                tweet_id,
+                ticket,
+                amount,
+            }),
+        };
+        let tip_1 = tip(1, format!("2{}", "0".repeat(63)), 55);
+        let tip_2 = tip(12, format!("{}5", "0".repeat(63)), 5);
+
+        assert_ne!(tip_1.hash().to_string(), tip_2.hash().to_string());
+    }
+
+    #[test]
+    fn test_post_tweet_hash_is_unambiguous() {
+        // Without separators, both tweets would be hashed with 10image/png 5 hash;
+        let author = PublicKeyHash::from_b58("tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH").unwrap();
+        let post = |royalties, mime_type: &str| Inner {
+            nonce: Nonce::default(),
+            content: Content::PostTweet(PostTweet {
+                author: author.clone(),
+                content: "Hello world".to_string(),
+                royalties,
+                media: vec![Media {
+                    mime_type: mime_type.to_string(),
+                    size: 5,
+                    hash: "hash".to_string(),
+                }],
+            }),
+        };
+        let post_1 = post(1, "0image/png");
+        let post_2 = post(10, "image/png");
+
+        assert_ne!(post_1.hash().to_string(), post_2.hash().to_string());
+    }
+
     fn decode_hex(s: &str) -> Result<Vec<u8>, ParseIntError> {
         (0..s.len())
             .step_by(2)
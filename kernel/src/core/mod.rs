@@ -1,6 +1,11 @@
 pub mod account;
+pub mod admin;
+pub mod deposit;
 pub mod error;
 pub mod hash;
+pub mod level;
+pub mod market;
+pub mod media;
 pub mod message;
 pub mod nonce;
 pub mod public_key;
@@ -5,7 +5,7 @@ use crate::core::public_key::PublicKey;
 
 use crate::core::{error::Error, hash::Blake2b20};
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone)]
 pub enum PublicKeyHash {
     Tz1(ContractTz1Hash),
 }
@@ -26,6 +26,24 @@ impl PublicKeyHash {
             None => Err(Error::StateDeserializarion),
         }
     }
+
+    /// Binary representation of the public key hash
+    ///
+    /// A tag for the kind of key (0x00 for tz1), followed by the 20 bytes of the hash
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            PublicKeyHash::Tz1(tz1) => [&[0x00], tz1.0.as_slice()].concat(),
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        match bytes {
+            [0x00, hash @ ..] => ContractTz1Hash::try_from(hash)
+                .map(PublicKeyHash::Tz1)
+                .map_err(|_| Error::StateDeserializarion),
+            _ => Err(Error::StateDeserializarion),
+        }
+    }
 }
 
 impl From<PublicKey> for PublicKeyHash {
@@ -91,4 +109,14 @@ mod tests {
 
         assert_eq!(tz1, &result.to_string())
     }
+
+    #[test]
+    fn test_tz1_bytes_roundtrip() {
+        let tz1 = PublicKeyHash::from_b58("tz1QFD9WqLWZmmAuqnnTPPUjfauitYEWdshv").unwrap();
+        let bytes = tz1.to_bytes();
+        assert_eq!(bytes.len(), 21);
+
+        let result = PublicKeyHash::from_bytes(&bytes).unwrap();
+        assert_eq!(tz1.to_string(), result.to_string());
+    }
 }
@@ -1,20 +1,135 @@
+use crate::core::admin::AdminContent;
 use crate::core::error::*;
 use crate::core::hash::*;
+use crate::core::message::{Content, InboxPosition};
+
+/// The kind of operation processed by the kernel
+#[derive(Clone, Copy)]
+pub enum Operation {
+    PostTweet,
+    LikeTweet,
+    Transfer,
+    Collect,
+    DeleteTweet,
+    TweetDeposit,
+    TicketDeposit,
+    Withdraw,
+    Tip,
+    ListTweet,
+    CancelListing,
+    BuyTweet,
+    OpenAuction,
+    Bid,
+    SetL1Contract,
+    SetReceiptRetention,
+    Pause,
+    Unpause,
+    AddModerator,
+    RemoveModerator,
+    UpgradeKernel,
+    PostLongTweet,
+}
+
+impl Operation {
+    /// Returns a stable numeric code of the operation
+    pub fn code(&self) -> u16 {
+        match self {
+            Operation::PostTweet => 0,
+            Operation::LikeTweet => 1,
+            Operation::Transfer => 2,
+            Operation::Collect => 3,
+            Operation::DeleteTweet => 5,
+            Operation::TweetDeposit => 6,
+            Operation::TicketDeposit => 7,
+            Operation::Withdraw => 8,
+            Operation::Tip => 9,
+            Operation::ListTweet => 10,
+            Operation::CancelListing => 11,
+            Operation::BuyTweet => 12,
+            Operation::OpenAuction => 13,
+            Operation::Bid => 14,
+            Operation::SetL1Contract => 15,
+            Operation::SetReceiptRetention => 16,
+            Operation::Pause => 17,
+            Operation::Unpause => 18,
+            Operation::AddModerator => 19,
+            Operation::RemoveModerator => 20,
+            Operation::UpgradeKernel => 21,
+            Operation::PostLongTweet => 22,
+        }
+    }
+}
+
+impl<'a> From<&'a Content> for Operation {
+    fn from(content: &'a Content) -> Self {
+        match content {
+            Content::PostTweet(_) => Operation::PostTweet,
+            Content::PostLongTweet(_) => Operation::PostLongTweet,
+            Content::LikeTweet(_) => Operation::LikeTweet,
+            Content::Transfer(_) => Operation::Transfer,
+            Content::Collect(_) => Operation::Collect,
+            Content::Withdraw(_) => Operation::Withdraw,
+            Content::Tip(_) => Operation::Tip,
+            Content::ListTweet(_) => Operation::ListTweet,
+            Content::CancelListing(_) => Operation::CancelListing,
+            Content::BuyTweet(_) => Operation::BuyTweet,
+            Content::OpenAuction(_) => Operation::OpenAuction,
+            Content::Bid(_) => Operation::Bid,
+            Content::DeleteTweet(_) => Operation::DeleteTweet,
+        }
+    }
+}
+
+impl<'a> From<&'a AdminContent> for Operation {
+    fn from(content: &'a AdminContent) -> Self {
+        match content {
+            AdminContent::SetL1Contract(_) => Operation::SetL1Contract,
+            AdminContent::SetReceiptRetention(_) => Operation::SetReceiptRetention,
+            AdminContent::Pause => Operation::Pause,
+            AdminContent::Unpause => Operation::Unpause,
+            AdminContent::AddModerator(_) => Operation::AddModerator,
+            AdminContent::RemoveModerator(_) => Operation::RemoveModerator,
+            AdminContent::UpgradeKernel(_) => Operation::UpgradeKernel,
+        }
+    }
+}
+
+/// What a successful operation has produced
+#[derive(Clone, Copy)]
+pub enum OperationResult {
+    /// A tweet has been created with the given id
+    TweetCreated(u64),
+    /// The operation does not produce any value
+    Unit,
+}
 
 /// definition of a receipt
 ///
-/// For now we don't need to know the cause of the error
-/// Because the receipt is only used in the front-end application to give user feedbacks
+/// A receipt is stored for each processed message,
+/// so that the front-end application can give feedbacks to the user
 pub struct Receipt {
     hash: Blake2b,
-    success: bool,
+    position: InboxPosition,
+    operation: Option<Operation>,
+    result: std::result::Result<OperationResult, u16>,
 }
 
 impl Receipt {
-    pub fn new(hash: Blake2b, result: &Result<()>) -> Receipt {
+    pub fn new(
+        hash: Blake2b,
+        position: InboxPosition,
+        operation: Option<Operation>,
+        result: &Result<OperationResult>,
+    ) -> Receipt {
+        let result = match result {
+            Ok(operation_result) => Ok(*operation_result),
+            Err(err) => Err(err.code()),
+        };
         Receipt {
             hash,
-            success: result.is_ok(),
+            position,
+            operation,
+            result,
         }
     }
 
@@ -25,6 +140,28 @@ impl Receipt {
 
     /// Returns a boolean that indicates if the receipt is a success or not
     pub fn success(&self) -> bool {
-        self.success
+        self.result.is_ok()
+    }
+
+    /// Returns the position of the message in the inbox
+    pub fn position(&self) -> &InboxPosition {
+        &self.position
+    }
+
+    /// Returns the kind of the processed operation
+    ///
+    /// The kind is unknown when the message cannot be decoded
+    pub fn operation(&self) -> Option<&Operation> {
+        self.operation.as_ref()
+    }
+
+    /// Returns the code of the error, if the operation has failed
+    pub fn error_code(&self) -> Option<u16> {
+        self.result.err()
+    }
+
+    /// Returns the result of the operation, if the operation has succeeded
+    pub fn operation_result(&self) -> Option<&OperationResult> {
+        self.result.as_ref().ok()
     }
 }
@@ -1,32 +1,79 @@
-use serde::Serialize;
-
+use crate::constants::COLLECT_FINALITY_DELAY;
 use crate::core::public_key_hash::PublicKeyHash;
 
 use super::message::PostTweet;
 
-#[derive(Serialize)]
+/// The immutable part of a tweet
+///
+/// The counters of a tweet (likes) are stored separately,
+/// So that updating them does not rewrite the content
 pub struct Tweet {
     pub author: PublicKeyHash,
     pub content: String,
-    pub likes: u64,
 }
 
 impl From<PostTweet> for Tweet {
     fn from(post_tweet: PostTweet) -> Self {
-        let PostTweet { author, content } = post_tweet;
-        Tweet {
-            author,
-            content,
-            likes: 0,
-        }
+        let PostTweet {
+            author, content, ..
+        } = post_tweet;
+        Tweet { author, content }
     }
 }
 
-impl Tweet {
-    pub fn like(self) -> Self {
-        Self {
-            likes: self.likes + 1,
-            ..self
+/// Life cycle of a tweet
+///
+/// A tweet is active until it is collected by its owner
+/// Then it is frozen: the tweet cannot be transferred or liked on the rollup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TweetState {
+    /// The tweet can be transferred and liked
+    Active,
+    /// The tweet has been collected at the given level,
+    /// The outbox message minting it on layer 1 is not yet final
+    Collecting(u32),
+    /// The outbox message minting the tweet on layer 1 is final
+    Collected,
+}
+
+impl TweetState {
+    /// Returns the state of the tweet at the given level
+    ///
+    /// A collecting tweet becomes collected after COLLECT_FINALITY_DELAY levels
+    pub fn at_level(self, level: u32) -> TweetState {
+        match self {
+            TweetState::Collecting(collect_level)
+                if level >= collect_level.saturating_add(COLLECT_FINALITY_DELAY) =>
+            {
+                TweetState::Collected
+            }
+            state => state,
         }
     }
+
+    /// Returns true if the tweet can still be updated on the rollup
+    pub fn is_active(&self) -> bool {
+        self == &TweetState::Active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TweetState;
+    use crate::constants::COLLECT_FINALITY_DELAY;
+
+    #[test]
+    fn test_collecting_becomes_collected() {
+        let state = TweetState::Collecting(10);
+        assert_eq!(state.at_level(10), TweetState::Collecting(10));
+        assert_eq!(
+            state.at_level(10 + COLLECT_FINALITY_DELAY - 1),
+            TweetState::Collecting(10)
+        );
+        assert_eq!(
+            state.at_level(10 + COLLECT_FINALITY_DELAY),
+            TweetState::Collected
+        );
+        assert_eq!(TweetState::Active.at_level(1000), TweetState::Active);
+    }
 }
@@ -1,32 +1,185 @@
-use crate::core::message::{Content, Message};
+use crate::constants::MAX_KERNEL_PAGES_PER_RUN;
+use crate::core::account::Account;
+use crate::core::admin::{AdminContent, AdminMessage};
+use crate::core::message::{Content, Input, Message};
 use crate::core::public_key_hash::PublicKeyHash;
-use crate::core::receipt::Receipt;
+use crate::core::receipt::{Operation, OperationResult, Receipt};
 
 // src/lib.rs
 use host::{rollup_core::RawRollupCore, runtime::Runtime};
 use kernel::kernel_entry;
-use storage::{read_account, store_account, store_receipt};
+use storage::{
+    add_admin_receipt, add_receipt_to_account, is_paused, is_upgrade_pending, read_account,
+    read_admin, read_admin_nonce, read_stored_level_info, request_reboot, set_moderator, set_paused,
+    store_account, store_admin_nonce, store_level_info, store_receipt, store_receipt_retention,
+    take_resume_flag, Storage, Transaction,
+};
+use ticks::{
+    TickCounter, APPLY_OPERATION_TICKS, KERNEL_PAGE_TICKS, MESSAGE_TICKS, READ_INPUT_TICKS,
+    REVEAL_LONG_TWEET_TICKS, SETTLE_AUCTION_TICKS, START_OF_LEVEL_TICKS, STORE_RECEIPT_TICKS,
+    VERIFY_SIGNATURE_TICKS,
+};
 
 mod constants;
 mod core;
 mod stages;
 mod storage;
+mod ticks;
 
 use crate::core::error::*;
+use crate::core::level::LevelInfo;
 use stages::{
-    create_tweet, get_previous_block_hash, like_tweet, read_input, transfer_tweet, verify_nonce,
-    verify_signature, withdraw_tweet,
+    buy_tweet, cancel_listing, create_long_tweet, create_tweet, delete_tweet, deposit_ticket,
+    deposit_tweet, install_kernel_upgrade, like_tweet, list_tweet, open_auction, place_bid,
+    prune_receipts, read_input, read_level_info, schedule_kernel_upgrade, set_l1_contract,
+    settle_auctions, tip_tweet, transfer_tweet, verify_admin, verify_long_tweet, verify_nonce,
+    verify_signature, withdraw_ticket, withdraw_tweet,
 };
 
+/// Apply the content of a message to the durable state
+///
+/// Returns the result of the operation
+fn apply<S: Storage>(
+    store: &mut S,
+    account: &Account,
+    content: Content,
+    level_info: &LevelInfo,
+) -> Result<OperationResult> {
+    let level = level_info.level;
+    match content {
+        Content::PostTweet(post_tweet) => {
+            let tweet_id = create_tweet(store, account, post_tweet)?;
+            Ok(OperationResult::TweetCreated(tweet_id))
+        }
+        Content::PostLongTweet(post_long_tweet) => {
+            let tweet_id = create_long_tweet(store, account, post_long_tweet)?;
+            Ok(OperationResult::TweetCreated(tweet_id))
+        }
+        Content::LikeTweet(tweet_id) => {
+            let () = like_tweet(store, level, account, &tweet_id)?;
+            Ok(OperationResult::Unit)
+        }
+        Content::Transfer(transfer) => {
+            let () = transfer_tweet(store, level, account, &transfer)?;
+            Ok(OperationResult::Unit)
+        }
+        Content::Collect(tweet_id) => {
+            let () = withdraw_tweet(store, level_info, account, &tweet_id)?;
+            Ok(OperationResult::Unit)
+        }
+        Content::Withdraw(withdraw) => {
+            let () = withdraw_ticket(store, account, withdraw)?;
+            Ok(OperationResult::Unit)
+        }
+        Content::Tip(tip) => {
+            let () = tip_tweet(store, account, tip)?;
+            Ok(OperationResult::Unit)
+        }
+        Content::ListTweet(sale) => {
+            let () = list_tweet(store, level, account, sale)?;
+            Ok(OperationResult::Unit)
+        }
+        Content::CancelListing(tweet_id) => {
+            let () = cancel_listing(store, account, &tweet_id)?;
+            Ok(OperationResult::Unit)
+        }
+        Content::BuyTweet(sale) => {
+            let () = buy_tweet(store, level, account, sale)?;
+            Ok(OperationResult::Unit)
+        }
+        Content::OpenAuction(auction) => {
+            let () = open_auction(store, level, account, auction)?;
+            Ok(OperationResult::Unit)
+        }
+        Content::Bid(bid) => {
+            let () = place_bid(store, level, account, bid)?;
+            Ok(OperationResult::Unit)
+        }
+        Content::DeleteTweet(tweet_id) => {
+            let () = delete_tweet(store, level, account, &tweet_id)?;
+            Ok(OperationResult::Unit)
+        }
+    }
+}
+
+/// Apply the content of an admin message to the durable state
+fn apply_admin<S: Storage>(store: &mut S, content: AdminContent) -> Result<OperationResult> {
+    match content {
+        AdminContent::SetL1Contract(l1_contract) => {
+            let () = set_l1_contract(store, l1_contract)?;
+            Ok(OperationResult::Unit)
+        }
+        AdminContent::SetReceiptRetention(retention) => {
+            let () = store_receipt_retention(store, &retention)?;
+            Ok(OperationResult::Unit)
+        }
+        AdminContent::Pause => {
+            let () = set_paused(store, true)?;
+            Ok(OperationResult::Unit)
+        }
+        AdminContent::Unpause => {
+            let () = set_paused(store, false)?;
+            Ok(OperationResult::Unit)
+        }
+        AdminContent::AddModerator(moderator) => {
+            let () = set_moderator(store, &moderator, true)?;
+            Ok(OperationResult::Unit)
+        }
+        AdminContent::RemoveModerator(moderator) => {
+            let () = set_moderator(store, &moderator, false)?;
+            Ok(OperationResult::Unit)
+        }
+        AdminContent::UpgradeKernel(root_hash) => {
+            let () = schedule_kernel_upgrade(store, root_hash)?;
+            Ok(OperationResult::Unit)
+        }
+    }
+}
+
+/// Process a message signed by the admin
+///
+/// As for the users, the admin nonce is incremented even if the operation fails
+/// And the receipt is indexed by the nonce of the admin
+fn admin_step<Host: RawRollupCore>(
+    host: &mut Host,
+    message: AdminMessage,
+) -> Result<OperationResult> {
+    let hash = message.hash();
+    let admin = read_admin(host)?;
+    let nonce = read_admin_nonce(host)?;
+    let content = verify_admin(message, admin.as_ref(), &nonce)?;
+    let nonce = nonce.next();
+    let () = store_admin_nonce(host, &nonce)?;
+    let () = add_admin_receipt(host, &nonce, &hash)?;
+    Transaction::run(host, |transaction| apply_admin(transaction, content))
+}
+
 /// A step is processing only one message from the inbox
 ///
 /// It will execute several sub steps:
 /// - verify the signature of the message
 /// - verify the nonce of the message
-/// - handle the message
-fn step<Host: RawRollupCore>(host: &mut Host, message: Message, previous_hash: &str) -> Result<()> {
+/// - index the receipt of the message in the account
+/// - handle the message in a transaction
+///
+/// The nonce is incremented even if the operation fails
+/// But the changes of a failed operation are discarded
+///
+/// While the rollup is paused, the message is rejected before its nonce is checked,
+/// So that it can be sent again once the rollup is unpaused
+///
+/// Returns the result of the operation
+fn step<Host: RawRollupCore>(
+    host: &mut Host,
+    message: Message,
+    level_info: &LevelInfo,
+) -> Result<OperationResult> {
+    if is_paused(host)? {
+        return Err(Error::Paused);
+    }
     let public_key = message.public_key();
     let public_key_hash = PublicKeyHash::from(public_key);
+    let hash = message.hash();
     host.write_debug("Message is deserialized\n");
 
     let inner = verify_signature(message)?;
@@ -37,57 +190,190 @@ fn step<Host: RawRollupCore>(host: &mut Host, message: Message, previous_hash: &
     let content = verify_nonce(inner, account.nonce())?;
     let account = account.increment_nonce();
     let _ = store_account(host, &account)?;
+    let () = add_receipt_to_account(host, &account.public_key_hash, account.nonce(), &hash)?;
 
-    // Interpret the message
-    let () = match content {
-        Content::PostTweet(post_tweet) => create_tweet(host, &account, post_tweet)?,
-        Content::LikeTweet(tweet_id) => like_tweet(host, &account, &tweet_id)?,
-        Content::Transfer(transfer) => transfer_tweet(host, &account, &transfer)?,
-        Content::Collect(twwet_id) => withdraw_tweet(host, &previous_hash, &account, &twwet_id)?,
-    };
+    if let Content::PostLongTweet(post_long_tweet) = &content {
+        let () = verify_long_tweet(host, post_long_tweet)?;
+    }
 
-    Ok(())
+    // Interpret the message
+    Transaction::run(host, |transaction| {
+        apply(transaction, &account, content, level_info)
+    })
 }
 
 /// Process all the inbox
 ///
 /// Read a message, process the error of the read message
+/// If the message cannot be deserialized, a receipt is stored under the hash of its payload
 /// If the message is correctly deserialized it continue the execution
 /// Then all the errors, will be stored in a receipt
 /// Continue until the inbox is emptied
 ///
-/// This function stop its execution when a RuntimeError happens
+/// Before reading a message, the ticks needed to process it are estimated
+/// If the run does not have enough ticks left, a reboot is requested
+/// And the processing of the inbox continues during the next run
+///
+/// The ended auctions are settled before the first message
 ///
-/// TODO: it can count ticks and reboot the kernel between two inbox message
-fn execute<Host: RawRollupCore>(host: &mut Host, previous_hash: &str) -> Result<()> {
-    let message = read_input(host);
-    match message {
-        Err(ReadInputError::EndOfInbox) => Ok(()),
-        Err(ReadInputError::Runtime(err)) => Err(Error::Runtime(err)),
-        Err(_) => execute(host, previous_hash),
-        Ok(message) => {
-            // If the message is processed we can extract the hash of the message
-            let hash = message.hash();
-            let result = step(host, message, previous_hash);
-
-            let receipt = Receipt::new(hash, &result);
-            let _ = store_receipt(host, &receipt)?;
-
-            match result {
-                Err(Error::Runtime(err)) => Err(Error::Runtime(err)),
-                Err(_) => execute(host, previous_hash),
-                Ok(()) => execute(host, previous_hash),
+/// While the rollup is paused, the inbox is still drained and the receipts are stored
+/// But the operations of the users fail, only the deposits and the admin operations are applied
+/// The ended auctions are not settled either, they are settled once the rollup is unpaused
+///
+/// This function stop its execution when a RuntimeError happens
+fn execute<Host: RawRollupCore>(
+    host: &mut Host,
+    ticks: &mut TickCounter,
+    level_info: &LevelInfo,
+) -> Result<()> {
+    if !is_paused(host)? {
+        let settled = settle_auctions(host, level_info.level)?;
+        ticks.consume(settled * SETTLE_AUCTION_TICKS);
+    }
+
+    loop {
+        if !ticks.has_budget_for(MESSAGE_TICKS) {
+            host.write_debug("Not enough ticks, rebooting\n");
+            return request_reboot(host);
+        }
+
+        let message = read_input(host);
+        ticks.consume(READ_INPUT_TICKS);
+
+        match message {
+            Err(ReadInputError::EndOfInbox) => return Ok(()),
+            Err(ReadInputError::Runtime(err)) => return Err(Error::Runtime(err)),
+            Err(ReadInputError::Malformed(position, hash, err)) => {
+                // The operation is unknown, but the user still receive a receipt
+                let receipt = Receipt::new(hash, position, None, &Err(err));
+                let _ = store_receipt(host, &receipt)?;
+                ticks.consume(STORE_RECEIPT_TICKS);
+            }
+            Err(ReadInputError::NotATzwitterMessage) => {}
+            Ok((position, Input::TweetDeposit(hash, deposit))) => {
+                let result = Transaction::run(host, |transaction| {
+                    deposit_tweet(transaction, level_info.level, &deposit)
+                });
+                ticks.consume(APPLY_OPERATION_TICKS);
+
+                let result = result.map(|()| OperationResult::Unit);
+                let operation = Operation::TweetDeposit;
+                let receipt = Receipt::new(hash, position, Some(operation), &result);
+                let _ = store_receipt(host, &receipt)?;
+                ticks.consume(STORE_RECEIPT_TICKS);
+
+                if let Err(Error::Runtime(err)) = result {
+                    return Err(Error::Runtime(err));
+                }
+            }
+            Ok((position, Input::TicketDeposit(hash, deposit))) => {
+                let result = Transaction::run(host, |transaction| {
+                    deposit_ticket(transaction, &deposit)
+                });
+                ticks.consume(APPLY_OPERATION_TICKS);
+
+                let result = result.map(|()| OperationResult::Unit);
+                let operation = Operation::TicketDeposit;
+                let receipt = Receipt::new(hash, position, Some(operation), &result);
+                let _ = store_receipt(host, &receipt)?;
+                ticks.consume(STORE_RECEIPT_TICKS);
+
+                if let Err(Error::Runtime(err)) = result {
+                    return Err(Error::Runtime(err));
+                }
+            }
+            Ok((position, Input::AdminMessage(message))) => {
+                let hash = message.hash();
+                let operation = Operation::from(&message.inner.content);
+                let result = admin_step(host, message);
+                ticks.consume(VERIFY_SIGNATURE_TICKS + APPLY_OPERATION_TICKS);
+                let receipt = Receipt::new(hash, position, Some(operation), &result);
+                let _ = store_receipt(host, &receipt)?;
+                ticks.consume(STORE_RECEIPT_TICKS);
+                if let Err(Error::Runtime(err)) = result {
+                    return Err(Error::Runtime(err));
+                }
             }
+            Ok((position, Input::Governance(hash, content))) => {
+                let operation = Operation::from(&content);
+                let result =
+                    Transaction::run(host, |transaction| apply_admin(transaction, content));
+                ticks.consume(APPLY_OPERATION_TICKS);
+                let receipt = Receipt::new(hash, position, Some(operation), &result);
+                let _ = store_receipt(host, &receipt)?;
+                ticks.consume(STORE_RECEIPT_TICKS);
+                if let Err(Error::Runtime(err)) = result {
+                    return Err(Error::Runtime(err));
+                }
+            }
+            Ok((position, Input::Message(message))) => {
+                // If the message is processed we can extract the hash of the message
+                let hash = message.hash();
+                let operation = Operation::from(&message.inner().content);
+                if let Content::PostLongTweet(_) = message.inner().content {
+                    ticks.consume(REVEAL_LONG_TWEET_TICKS);
+                }
+                let result = step(host, message, level_info);
+                ticks.consume(VERIFY_SIGNATURE_TICKS + APPLY_OPERATION_TICKS);
+
+                let receipt = Receipt::new(hash, position, Some(operation), &result);
+                let _ = store_receipt(host, &receipt)?;
+                ticks.consume(STORE_RECEIPT_TICKS);
+
+                if let Err(Error::Runtime(err)) = result {
+                    return Err(Error::Runtime(err));
+                }
+            }
+        }
+    }
+}
+
+/// Prepare the processing of the inbox
+///
+/// At the beginning of a level, the level information is read from the inbox and stored
+/// The transient state of an incomplete transaction is deleted and the old receipts are pruned
+///
+/// After a reboot, the beginning of the inbox has already been consumed,
+/// So the level information is read from the durable state
+///
+/// Then the pages of a scheduled kernel upgrade are revealed
+/// If the kernel is not entirely revealed, a reboot is requested to continue the installation
+fn start_level<Host: RawRollupCore>(host: &mut Host, ticks: &mut TickCounter) -> Result<LevelInfo> {
+    let is_resuming = take_resume_flag(host)?;
+    let level_info = if is_resuming {
+        read_stored_level_info(host)?
+    } else {
+        let level_info = read_level_info(host)?;
+        let () = store_level_info(host, &level_info)?;
+        let () = Transaction::clean_up(host)?;
+
+        // Pruning old receipts should not prevent the inbox to be processed
+        if let Err(err) = prune_receipts(host, level_info.level) {
+            host.write_debug(&err.to_string())
         }
+        ticks.consume(START_OF_LEVEL_TICKS);
+        level_info
+    };
+
+    // A failed upgrade is discarded, the current kernel keeps running
+    match install_kernel_upgrade(host, MAX_KERNEL_PAGES_PER_RUN) {
+        Ok(revealed) => ticks.consume(revealed * KERNEL_PAGE_TICKS),
+        Err(err) => host.write_debug(&err.to_string()),
+    }
+    if is_upgrade_pending(host)? {
+        let () = request_reboot(host)?;
     }
+
+    Ok(level_info)
 }
 
 fn entry<Host: RawRollupCore>(host: &mut Host) {
     host.write_debug("Hello Kernel\n");
 
-    match get_previous_block_hash(host) {
+    let mut ticks = TickCounter::default();
+    match start_level(host, &mut ticks) {
         Err(err) => host.write_debug(&err.to_string()),
-        Ok(previous_hash) => match execute(host, &previous_hash) {
+        Ok(level_info) => match execute(host, &mut ticks, &level_info) {
             Ok(()) => {}
             Err(err) => host.write_debug(&err.to_string()),
         },
@@ -98,21 +384,79 @@ kernel_entry!(entry);
 
 #[cfg(test)]
 mod tests {
+    use crypto::hash::{ContractKt1Hash, SmartRollupHash};
     use host::{path::RefPath, rollup_core::RawRollupCore, runtime::Runtime};
     use mock_runtime::{host::MockHost, state::HostState};
+    use tezos_rollup_encoding::{dac::prepare_preimages, smart_rollup::SmartRollupAddress};
 
     use crate::{
-        constants::MAGIC_BYTE,
-        core::message::Message,
-        stages::read_input,
+        admin_step,
+        constants::{
+            ADMIN_MAGIC_BYTE, COLLECT_FINALITY_DELAY, MAGIC_BYTE, MAX_KERNEL_PAGES_PER_RUN,
+            MAX_LONG_TWEET_SIZE,
+        },
+        core::error::{Error, ReadInputError},
+        core::admin::AdminMessage,
+        core::level::LevelInfo,
+        core::message::{Input, Message},
+        core::public_key_hash::PublicKeyHash,
+        execute,
+        stages::{
+            deposit_ticket, deposit_tweet, install_kernel_upgrade, prune_receipts, read_input,
+            schedule_kernel_upgrade,
+        },
         step,
-        storage::{exists, read_u64},
+        storage::{
+            read_owner, read_tweet, set_paused, whitelisted_ticketer, Flag, Key, Storage, ADMIN,
+            GOVERNANCE, L1_CONTRACT, ROLLUP_ADDRESS,
+        },
+        ticks::{TickCounter, MAX_TICKS},
     };
 
+    fn level_info() -> LevelInfo {
+        LevelInfo {
+            level: 0,
+            previous_hash: "previous_hash".to_string(),
+        }
+    }
+
+    /// Write the configuration that is set at install time
+    ///
+    /// The admin is tz1XW83nyuhuQFyVMSRu1G1S2JhqGdES7qou
+    /// The governance contract is KT1LzC7jMi6Q4K1Sfy9CJcDAnWm8JLEE2JmZ
+    /// And the ticketer is KT1UccUhhU6X3rLDtu691TZLHSbKKPxTpNFQ
+    ///
+    /// The address of the rollup is the one the deposits of the tests are sent to
+    fn install<Host: RawRollupCore + Runtime>(host: &mut Host) {
+        let l1_contract = "KT1RycYvM4EVs6BAXWEsGXaAaRqiMP53KT4w".to_string();
+        let admin = PublicKeyHash::from_b58("tz1XW83nyuhuQFyVMSRu1G1S2JhqGdES7qou").unwrap();
+        let governance =
+            ContractKt1Hash::from_b58check("KT1LzC7jMi6Q4K1Sfy9CJcDAnWm8JLEE2JmZ").unwrap();
+        let rollup_address = SmartRollupAddress::new(SmartRollupHash(vec![0; 20])).to_b58check();
+        L1_CONTRACT.write(host, &l1_contract).unwrap();
+        ADMIN.write(host, &admin).unwrap();
+        GOVERNANCE.write(host, &governance).unwrap();
+        ROLLUP_ADDRESS.write(host, &rollup_address).unwrap();
+        whitelisted_ticketer("KT1UccUhhU6X3rLDtu691TZLHSbKKPxTpNFQ")
+            .unwrap()
+            .write(host, &Flag)
+            .unwrap();
+    }
+
+    /// Change the destination of a deposit to another rollup
+    ///
+    /// The destination is the last 20 bytes of the inbox message
+    fn to_other_rollup(mut input: Vec<u8>) -> Vec<u8> {
+        if let Some(last) = input.last_mut() {
+            *last = 0x01;
+        }
+        input
+    }
+
     /// Assert a path exists in the storage
     fn assert_exist<Host: RawRollupCore + Runtime>(host: &mut Host, path: &str) {
         let path = RefPath::assert_from(path.as_bytes());
-        let is_present = exists(host, &path).unwrap();
+        let is_present = host.has(&path).unwrap();
         assert!(is_present);
     }
 
@@ -123,13 +467,13 @@ mod tests {
         expected: Option<u64>,
     ) {
         let path = RefPath::assert_from(path.as_bytes());
-        let value = read_u64(host, &path).unwrap();
+        let value = Key::<u64, _>::new(path).read(host).unwrap();
         assert_eq!(expected, value);
     }
 
     fn assert_not_exists<Host: RawRollupCore + Runtime>(host: &mut Host, path: &str) {
         let path = RefPath::assert_from(path.as_bytes());
-        let is_present = exists(host, &path).unwrap();
+        let is_present = host.has(&path).unwrap();
         assert!(!is_present)
     }
 
@@ -154,6 +498,20 @@ mod tests {
         hex::decode(msg).unwrap()
     }
 
+    /// Like of the tweet 0 signed by tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH with the nonce 1
+    fn input_like_other_account() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a22656473696774665973754b506341696e346842685976433835746a62747439384d454b58536e72614176767545724868484c4c444e785a33314542774d6932513668664638743331573879533445644837593671796277463337346d7854654d6f3251227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b224c696b655477656574223a307d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Same like signed by tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT with the nonce 1
+    fn input_like_third_account() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b7564386f57456d73696f416678794674343561503374586e635a474b514d4a4d6733615277537478774c464e4766464b6575227d2c227369676e6174757265223a7b2245643235353139223a22656473696774785459335955586b36776b784d4779714178417a6f6d32713731545678477332634451726a394e68753869314d757a6b74537a47754131574c72635846387965735175525663346d68356b373857434d627432626935375979735a6d6a227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b224c696b655477656574223a307d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
     fn input_like_2() -> Vec<u8> {
         let input = "7b22706b6579223a7b2245643235353139223a226564706b75444d556d375935337770346778654c425875694168585a724c6e385842315238336b737676657348384c7038626d43664b227d2c227369676e6174757265223a7b2245643235353139223a22656473696774775a6d6376566470575361696836646a5057526172645668723154614b32786275646a7937686d7a6a65456e4b77766747346d50676455573478764254714452584e5348596f6a5973395a796d5968565469586d667a67323778624846227d2c22696e6e6572223a7b226e6f6e6365223a332c22636f6e74656e74223a7b224c696b655477656574223a307d7d7d";
         let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
@@ -166,8 +524,231 @@ mod tests {
         hex::decode(msg).unwrap()
     }
 
+    /// PostTweet "Hello world" signed by tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH with the nonce 1
+    fn input_post_other_account() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a22656473696774786255537a7a4b7441467242785a4d7836443562523763675869555963744b5a4b475757674d6a6d51455679646735476e7750436469427833646d59684856635a44417577685775355974784b476843476553574174626b7854734e48227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b22506f73745477656574223a7b22617574686f72223a7b22547a31223a22747a3163385045444e666a3655786f514d325843796654484d354b624747676f71447248227d2c22636f6e74656e74223a2248656c6c6f20776f726c64227d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Collect of the tweet 0 with the nonce 2
+    fn input_collect() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a226564736967746f624d5543545a4439384e714b55344d704b46315a7a7838795a6a393171423553333362626348536464546d344c6b5464446e48517557517856797a4d4b59716a467042475035455044484a456a4442545a723432356b55684659514e227d2c22696e6e6572223a7b226e6f6e6365223a322c22636f6e74656e74223a7b22436f6c6c656374223a307d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Transfer of the tweet 0 to tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT with the nonce 3
+    fn input_transfer_collected() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a22656473696774687064546964445875744b6259647272546f317346624546756845396d786d4a3563686e64396877756b66534e4867483956355a4a47414374754347626e7443654678454a685a4c5470554b544542736334314b44724e4e3331675843227d2c22696e6e6572223a7b226e6f6e6365223a332c22636f6e74656e74223a7b225472616e73666572223a7b2264657374696e6174696f6e223a7b22547a31223a22747a31526d4e6d754e6d3979677341313752427a583338474a5747334265783555395554227d2c2274776565745f6964223a307d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Deposit of the tweet 0 for tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT sent by the layer 1 token contract
+    fn input_tweet_deposit() -> Vec<u8> {
+        hex::decode("00000508070700000a00000016000043346e326b6721be4a070bfb2eb49127322fa5e4becc2e32eca0e6fe70de0e3fb884e8f6aefffa3d00b4decd5a5f8f2ba708f8ced72eec89f44f3be96a0000000000000000000000000000000000000000").unwrap()
+    }
+
+    /// Update of the layer 1 contract to KT1BN5u3CeRzVC7UaJYF4ADYtiHFHzqwC8dV signed by the admin with the nonce 1
+    fn input_set_l1_contract() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b76536a356f61445144655a583963414a656d394a72734d48656f476f34463951736f7a647473436f4e5234446346326d554c227d2c227369676e6174757265223a7b2245643235353139223a226564736967746a34444c4147374b73684d37784a7433343632767a646447354b477844555775414e4670735442364d575543366872355a355968363833444a4e4536394e58576a32524547745077594d745a4274507866564864626e6e574a76345756227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b225365744c31436f6e7472616374223a7b2261646472657373223a224b5431424e3575334365527a56433755614a59463441445974694846487a717743386456222c22656e747279706f696e74223a226d696e74227d7d7d7d";
+        let msg = format!("01{:02x}{}", ADMIN_MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Update of the layer 1 contract signed by tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH, who is not the admin
+    fn input_set_l1_contract_not_admin() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a2265647369677472644c7937344e6d54385335614e4b4a46627776575269726b45725954547159374375587334646935656d76643331423971536e7472376844754633736f644c564c4853625579623769436178395573666338533731417a543132454e227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b225365744c31436f6e7472616374223a7b2261646472657373223a224b5431424e3575334365527a56433755614a59463441445974694846487a717743386456222c22656e747279706f696e74223a226d696e74227d7d7d7d";
+        let msg = format!("01{:02x}{}", ADMIN_MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Pause of the rollup signed by the admin with the nonce 1
+    fn input_pause() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b76536a356f61445144655a583963414a656d394a72734d48656f476f34463951736f7a647473436f4e5234446346326d554c227d2c227369676e6174757265223a7b2245643235353139223a226564736967745a3357616552525034436b6455756651436273414776355a654462636e743362427331336d596a4c336a4a7665393468627a51537651384a563938703935574d5544396f4e5958577563667853547a5875477a456d48546455766a5358227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a225061757365227d7d";
+        let msg = format!("01{:02x}{}", ADMIN_MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Long tweet with the nonce 1, whose body is "Hello world! " repeated 400 times
+    fn input_post_long_tweet() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a226564736967753547667833514339315541544a6a50376d63346f317562705354314b6250396178727355726a4232414642757172457147395861516f5a4c73655a4b78544d7238476f695042355a784b42325337377132363734575a50754e4c64644d227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b22506f73744c6f6e675477656574223a7b22617574686f72223a7b22547a31223a22747a3163385045444e666a3655786f514d325843796654484d354b624747676f71447248227d2c22626f6479223a22303039396430633435303761633065316130383338373165363433393435383033386636653639633937383437313931333430613334613030333035363462393736227d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Long tweet with the nonce 2, whose body is one byte larger than the maximum size
+    fn input_post_too_long_tweet() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a2265647369677466386250737754425a59334868596666455859777534384d623347673252456e5a5358646f7536423968583352766b53434446325a786a36597932416f333139685256724c555044557965633570793645465a7a71766f627a4b4d4642227d2c22696e6e6572223a7b226e6f6e6365223a322c22636f6e74656e74223a7b22506f73744c6f6e675477656574223a7b22617574686f72223a7b22547a31223a22747a3163385045444e666a3655786f514d325843796654484d354b624747676f71447248227d2c22626f6479223a22303034336138633131313261393132386663333530393632356330663938396436643639633238633332633537623231353761343932373534363266653763646531227d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Tweet with the nonce 1 with two media: a png revealed from preimages and a mp4 on IPFS
+    fn input_post_with_media() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a22656473696774635667375a48647268674a5433787541316948635844666734536562416d6238394546656d515758554271446145656251426b6e354c6754454841524a78626a7a70756e44655a6d445433517971644766474757447847525379655951227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b22506f73745477656574223a7b22617574686f72223a7b22547a31223a22747a3163385045444e666a3655786f514d325843796654484d354b624747676f71447248227d2c22636f6e74656e74223a224c6f6f6b2061742074686973222c226d65646961223a5b7b226d696d655f74797065223a22696d6167652f706e67222c2273697a65223a313032342c2268617368223a22303037643231386666303464626261646131336138376461646431363836666666613361633631376265373830346531353033653462306439346365613561316435227d2c7b226d696d655f74797065223a22766964656f2f6d7034222c2273697a65223a323034382c2268617368223a2262616679626569676479727a74357366703775646d37687537367568377932366e6633656675796c71616266336f636c67747179353566627a6469227d5d7d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Tweet with the nonce 2 with a media whose MIME type has no subtype
+    fn input_post_with_invalid_media() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a2265647369677468436d6f3944756d44504d51415a467736507753726a4b44514b4651465465586938347a6d524c776a576b6864556a353232667a51396e793441727442326e75686d4b726e6e443948736e57327a76486150774e394355654e6f537979227d2c22696e6e6572223a7b226e6f6e6365223a322c22636f6e74656e74223a7b22506f73745477656574223a7b22617574686f72223a7b22547a31223a22747a3163385045444e666a3655786f514d325843796654484d354b624747676f71447248227d2c22636f6e74656e74223a224c6f6f6b2061742074686973222c226d65646961223a5b7b226d696d655f74797065223a22696d616765222c2273697a65223a313032342c2268617368223a22516d597741504a7a7635435a736e4136323573335866326e656d7459675070486457457a37396f6a576e50626447227d5d7d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Upgrade of the kernel signed by the admin with the nonce 1
+    ///
+    /// The root hash is the one of the kernel "\0asm\x01\0\0\0"
+    fn input_upgrade_kernel() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b76536a356f61445144655a583963414a656d394a72734d48656f476f34463951736f7a647473436f4e5234446346326d554c227d2c227369676e6174757265223a7b2245643235353139223a22656473696775344a585068316334414c344a666f58773437326b6f55664551426257537678696e574b61384b6a6b39765a4e544b694c42684367384a69664a6370356f664551615753503865367a377659367165664c4848474261594341536b56615a227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b22557067726164654b65726e656c223a22303037643231386666303464626261646131336138376461646431363836666666613361633631376265373830346531353033653462306439346365613561316435227d7d7d";
+        let msg = format!("01{:02x}{}", ADMIN_MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Unpause of the rollup signed by the admin with the nonce 2
+    fn input_unpause() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b76536a356f61445144655a583963414a656d394a72734d48656f476f34463951736f7a647473436f4e5234446346326d554c227d2c227369676e6174757265223a7b2245643235353139223a2265647369677464754c477a64426852624b43467a674d6336594b78686a5162377a504b547a7557657831383374324d4c334e3337797a695858675a68436838396b694d73664e5448644879454a6f52343751593273734d355453337836507776747153227d2c22696e6e6572223a7b226e6f6e6365223a322c22636f6e74656e74223a22556e7061757365227d7d";
+        let msg = format!("01{:02x}{}", ADMIN_MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Moderator role given to tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT by the governance contract
+    fn input_add_moderator() -> Vec<u8> {
+        hex::decode("0000050505050a0000003f7b224164644d6f64657261746f72223a7b22547a31223a22747a31526d4e6d754e6d3979677341313752427a583338474a5747334265783555395554227d7d880f921ea4f995d96849df90a265da8a8823309800b4decd5a5f8f2ba708f8ced72eec89f44f3be96a0000000000000000000000000000000000000000").unwrap()
+    }
+
+    /// Deletion of the tweet 0 signed by tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT with the nonce 1
+    fn input_moderate() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b7564386f57456d73696f416678794674343561503374586e635a474b514d4a4d6733615277537478774c464e4766464b6575227d2c227369676e6174757265223a7b2245643235353139223a22656473696775336972616f674234344c4c71377441316361756562376446534e6577435939786654546d7a424761744e63387844696438634b486f4b66574a7174506e45325252416f65754c4a71666d77526b34416b5a7a5575394a6d64794a5a7674227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b2244656c6574655477656574223a307d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Deletion of the tweet 0 signed by its author, who is not a moderator, with the nonce 2
+    fn input_delete_not_moderator() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a2265647369677462366d714552685a59794b39724d6879764c5739364a50447063454e6367615a50667a6b7772453676774e416a7339513374705433486b784d417973764d715a58736f434a5338507a4d39555339324862456478363561447833514143227d2c22696e6e6572223a7b226e6f6e6365223a322c22636f6e74656e74223a7b2244656c6574655477656574223a307d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Deposit of 100 unit tickets for tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT sent by the whitelisted ticketer
+    fn input_ticket_deposit() -> Vec<u8> {
+        hex::decode("00000505050807070a00000016000043346e326b6721be4a070bfb2eb49127322fa5e407070a0000001601dbbbc30c8c9c32b62f22d8c5d62708235272cc13000707030b00a401dbbbc30c8c9c32b62f22d8c5d62708235272cc1300b4decd5a5f8f2ba708f8ced72eec89f44f3be96a0000000000000000000000000000000000000000").unwrap()
+    }
+
+    /// Withdrawal of 40 tickets to KT1ThEdxfUcWUwqsdergy3QnbCWGHSUHeHJq signed by tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT with the nonce 1
+    fn input_withdraw() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b7564386f57456d73696f416678794674343561503374586e635a474b514d4a4d6733615277537478774c464e4766464b6575227d2c227369676e6174757265223a7b2245643235353139223a22656473696774787853696b4a6275505947314c7a674c76755a6d7062366b765062316b59344d4d4e6a725147743531546353664e325154653556703142354c5768764a5a76676568356f726961575a68486448565045707a55454842396d5353547267227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b225769746864726177223a7b227469636b6574223a2237646265323830383132636631626636343136366531396239393763666263653265333034653939326236313761313765376231383864633034336530323062222c22616d6f756e74223a34302c2264657374696e6174696f6e223a224b543154684564786655635755777173646572677933516e624357474853554865484a71222c22656e747279706f696e74223a226275726e227d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Withdrawal of 100 tickets to KT1ThEdxfUcWUwqsdergy3QnbCWGHSUHeHJq signed by tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT with the nonce 2
+    fn input_withdraw_too_much() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b7564386f57456d73696f416678794674343561503374586e635a474b514d4a4d6733615277537478774c464e4766464b6575227d2c227369676e6174757265223a7b2245643235353139223a22656473696775345731355435366f7a6950746163787a693562415a4c777562414173635a574d33384366637a417942314d7546364d4b3556767748416a5054354375597469327769454262754a6a72354e74344846436e78375357756b797038325366227d2c22696e6e6572223a7b226e6f6e6365223a322c22636f6e74656e74223a7b225769746864726177223a7b227469636b6574223a2237646265323830383132636631626636343136366531396239393763666263653265333034653939326236313761313765376231383864633034336530323062222c22616d6f756e74223a3130302c2264657374696e6174696f6e223a224b543154684564786655635755777173646572677933516e624357474853554865484a71222c22656e747279706f696e74223a226275726e227d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Tip of 30 tickets to the author of the tweet 0 signed by tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT with the nonce 1
+    fn input_tip() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b7564386f57456d73696f416678794674343561503374586e635a474b514d4a4d6733615277537478774c464e4766464b6575227d2c227369676e6174757265223a7b2245643235353139223a226564736967746932486276686a3847696f5a4132754d716263723855614d6e66586655774b767038656633765967627a6d42697061745841637736726f45745066565a784341727045483479454241627961564d46485568326d79445343466739334c227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b22546970223a7b2274776565745f6964223a302c227469636b6574223a2237646265323830383132636631626636343136366531396239393763666263653265333034653939326236313761313765376231383864633034336530323062222c22616d6f756e74223a33307d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Tip of 10 tickets to the author of the tweet 5 signed by tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT with the nonce 2
+    fn input_tip_missing_tweet() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b7564386f57456d73696f416678794674343561503374586e635a474b514d4a4d6733615277537478774c464e4766464b6575227d2c227369676e6174757265223a7b2245643235353139223a2265647369677470766331614d4461746675766e6b7637474863546179465a797a705132344c625a7475573978784c7248614c444d45365768574b707a436f5476706964696e6550774236526f506d714231455741364a717968624c415a516176526578227d2c22696e6e6572223a7b226e6f6e6365223a322c22636f6e74656e74223a7b22546970223a7b2274776565745f6964223a352c227469636b6574223a2237646265323830383132636631626636343136366531396239393763666263653265333034653939326236313761313765376231383864633034336530323062222c22616d6f756e74223a31307d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Listing of the tweet 0 at 50 tickets signed by tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH with the nonce 2
+    fn input_list() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a22656473696775337232736a4d51756a4d50356b6b376f475848746762553358426b447851385a61706d5a753550476364527136616b34423773504a6145436334506447365769526b745631797a7942666539426f38437841655a79784d72727976414c227d2c22696e6e6572223a7b226e6f6e6365223a322c22636f6e74656e74223a7b224c6973745477656574223a7b2274776565745f6964223a302c227469636b6574223a2237646265323830383132636631626636343136366531396239393763666263653265333034653939326236313761313765376231383864633034336530323062222c227072696365223a35307d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Purchase of the tweet 0 at 50 tickets signed by tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT with the nonce 1
+    fn input_buy() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b7564386f57456d73696f416678794674343561503374586e635a474b514d4a4d6733615277537478774c464e4766464b6575227d2c227369676e6174757265223a7b2245643235353139223a226564736967745a52666866516666675937656467486e3950727174314c44544b396a53373358665272444b62413853317646614c4d727731784738514e754b51516d556b3742645633795644776d6d574359467453516562426b4764727639476e4553227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b224275795477656574223a7b2274776565745f6964223a302c227469636b6574223a2237646265323830383132636631626636343136366531396239393763666263653265333034653939326236313761313765376231383864633034336530323062222c227072696365223a35307d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Auction of the tweet 0 with a reserve of 20 tickets until the level 0 signed by tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH with the nonce 2
+    fn input_open_auction() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a226564736967747a697974567942487451717250517772314c377a5a474b6f686e786646707972654b3846376e5832313533617556775862615768484b597a434a3275464637526e796d43426357375435463936343251686855366e346b395062526768227d2c22696e6e6572223a7b226e6f6e6365223a322c22636f6e74656e74223a7b224f70656e41756374696f6e223a7b2274776565745f6964223a302c227469636b6574223a2237646265323830383132636631626636343136366531396239393763666263653265333034653939326236313761313765376231383864633034336530323062222c2272657365727665223a32302c22656e645f6c6576656c223a307d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Auction of the tweet 0 until the level 40_321, one level longer than the maximum duration
+    fn input_open_auction_too_long() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a22656473696775346e46473345557136777774517877504448484361527269514e6b4d395658794a6a5079694a76595362444d465746514a6f42477958435055323150336476634d7462773234376b356562395a774c736d6e72797641564c764e39785a227d2c22696e6e6572223a7b226e6f6e6365223a322c22636f6e74656e74223a7b224f70656e41756374696f6e223a7b2274776565745f6964223a302c227469636b6574223a2237646265323830383132636631626636343136366531396239393763666263653265333034653939326236313761313765376231383864633034336530323062222c2272657365727665223a32302c22656e645f6c6576656c223a34303332317d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Bid of 30 tickets on the tweet 0 signed by tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT with the nonce 1
+    fn input_bid() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b7564386f57456d73696f416678794674343561503374586e635a474b514d4a4d6733615277537478774c464e4766464b6575227d2c227369676e6174757265223a7b2245643235353139223a226564736967746e416550647a7757624e474e594848324e416978695964437658364333586f674759754876776b71614c76783351644a626f62506a56435550374565744442564d66317562786e506f786670757241786a646979535246684a696d3946227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b22426964223a7b2274776565745f6964223a302c22616d6f756e74223a33307d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Bid of 25 tickets on the tweet 0 signed by tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT with the nonce 2
+    fn input_bid_too_low() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b7564386f57456d73696f416678794674343561503374586e635a474b514d4a4d6733615277537478774c464e4766464b6575227d2c227369676e6174757265223a7b2245643235353139223a226564736967747565524d6b476141376b754d7a71794751796d57534d394847664b454c633463524c6d44357a5152647268456b7567756935644b7a4b454b5171394778556e565269524232584b315773775337514b796d6a67426e537158716e537679227d2c22696e6e6572223a7b226e6f6e6365223a322c22636f6e74656e74223a7b22426964223a7b2274776565745f6964223a302c22616d6f756e74223a32357d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Bid of 40 tickets on the tweet 0 signed by tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT with the nonce 3
+    fn input_outbid() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b7564386f57456d73696f416678794674343561503374586e635a474b514d4a4d6733615277537478774c464e4766464b6575227d2c227369676e6174757265223a7b2245643235353139223a2265647369677473344e67336e735a563466454e5776534c6d4839474b486d594859734d653772747a696e7966506331465a65715a4e4c347477486e64555264663337557977705876536b34335779623339633531553544427259453755517150435069227d2c22696e6e6572223a7b226e6f6e6365223a332c22636f6e74656e74223a7b22426964223a7b2274776565745f6964223a302c22616d6f756e74223a34307d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// PostTweet "Hello world" with 10% of royalties signed by tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH with the nonce 1
+    fn input_post_with_royalties() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a226564736967745a65697657425942364b68794e6b69463437714e5862704c594a31615375655776534a3732637953767367694e786632594a573832685151487635774e37725063746a4735516561656f6347364a6e426d435035346e43747a62723565227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b22506f73745477656574223a7b22617574686f72223a7b22547a31223a22747a3163385045444e666a3655786f514d325843796654484d354b624747676f71447248227d2c22636f6e74656e74223a2248656c6c6f20776f726c64222c22726f79616c74696573223a31307d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Transfer of the tweet 0 to tz1XW83nyuhuQFyVMSRu1G1S2JhqGdES7qou signed by tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH with the nonce 2
+    fn input_transfer_to_reseller() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b75684563776f4c79734c766f645278514c7a754d3341565a76437554366b6f566b5561685335336d4e426445384c6275476f227d2c227369676e6174757265223a7b2245643235353139223a226564736967746b6b776242715071447250476d77574b53795a76365456344d67425735645476424642464e51713857446f6556333434366d7432634d47706153634b57533650537a7258566e4d454e593176754c6d677965536b6d51626b7a63464d51227d2c22696e6e6572223a7b226e6f6e6365223a322c22636f6e74656e74223a7b225472616e73666572223a7b2274776565745f6964223a302c2264657374696e6174696f6e223a7b22547a31223a22747a31585738336e79756875514679564d53527531473153324a68714764455337716f75227d7d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
+    /// Sale of the tweet 0 at 50 tickets signed by tz1XW83nyuhuQFyVMSRu1G1S2JhqGdES7qou with the nonce 1
+    fn input_resale() -> Vec<u8> {
+        let input = "7b22706b6579223a7b2245643235353139223a226564706b76536a356f61445144655a583963414a656d394a72734d48656f476f34463951736f7a647473436f4e5234446346326d554c227d2c227369676e6174757265223a7b2245643235353139223a2265647369677477615641696e473646663935676d764c38445159363638583839686551664c41476453316a484c50703547686d766a4457323753476d517937696d6257336755436f31514c74474d4b7374505745736e3541794a346452336b36567467227d2c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b224c6973745477656574223a7b2274776565745f6964223a302c227469636b6574223a2237646265323830383132636631626636343136366531396239393763666263653265333034653939326236313761313765376231383864633034336530323062222c227072696365223a35307d7d7d7d";
+        let msg = format!("01{:02x}{}", MAGIC_BYTE, input);
+        hex::decode(msg).unwrap()
+    }
+
     fn next_input<Host: RawRollupCore + Runtime>(host: &mut Host) -> Message {
-        read_input(host).unwrap()
+        match read_input(host).unwrap() {
+            (_, Input::Message(message)) => message,
+            _ => panic!("Expected a signed message"),
+        }
+    }
+
+    fn next_admin_input<Host: RawRollupCore + Runtime>(host: &mut Host) -> AdminMessage {
+        match read_input(host).unwrap() {
+            (_, Input::AdminMessage(message)) => message,
+            _ => panic!("Expected an admin message"),
+        }
     }
 
     #[test]
@@ -181,11 +762,11 @@ mod tests {
         host.as_mut().add_next_inputs(0, inputs);
 
         let message = next_input(&mut host);
-        let res = step(&mut host, message, "previous_hash");
+        let res = step(&mut host, message, &level_info());
 
         assert!(res.is_ok());
 
-        assert_exist(&mut host, "/tweets/0");
+        assert_exist(&mut host, "/tweets/0/record");
         assert_u64(&mut host, "/tweets/0/likes", Some(0));
         assert_exist(
             &mut host,
@@ -195,6 +776,10 @@ mod tests {
             &mut host,
             "/accounts/tz1QFD9WqLWZmmAuqnnTPPUjfauitYEWdshv/tweets/written/0",
         );
+        assert_exist(
+            &mut host,
+            "/accounts/tz1QFD9WqLWZmmAuqnnTPPUjfauitYEWdshv/receipts/1",
+        );
     }
 
     #[test]
@@ -207,9 +792,9 @@ mod tests {
         host.as_mut().add_next_inputs(0, inputs);
 
         let message = next_input(&mut host);
-        let res1 = step(&mut host, message, "previous_hash");
+        let res1 = step(&mut host, message, &level_info());
         let message = next_input(&mut host);
-        let res2 = step(&mut host, message, "previous_hash");
+        let res2 = step(&mut host, message, &level_info());
 
         assert!(res1.is_ok());
         assert!(res2.is_err());
@@ -226,9 +811,9 @@ mod tests {
         host.as_mut().add_next_inputs(0, inputs);
 
         let message = next_input(&mut host);
-        let res_1 = step(&mut host, message, "previous_hash");
+        let res_1 = step(&mut host, message, &level_info());
         let message = next_input(&mut host);
-        let res_2 = step(&mut host, message, "previous_hash");
+        let res_2 = step(&mut host, message, &level_info());
 
         assert!(res_1.is_ok());
         assert!(res_2.is_ok());
@@ -250,9 +835,9 @@ mod tests {
         host.as_mut().add_next_inputs(0, inputs);
 
         let message = next_input(&mut host);
-        let res_1 = step(&mut host, message, "previous_hash");
+        let res_1 = step(&mut host, message, &level_info());
         let message = next_input(&mut host);
-        let res_2 = step(&mut host, message, "previous_hash");
+        let res_2 = step(&mut host, message, &level_info());
 
         assert!(res_1.is_ok());
         assert!(res_2.is_ok());
@@ -273,11 +858,11 @@ mod tests {
         host.as_mut().add_next_inputs(0, inputs);
 
         let message = next_input(&mut host);
-        let res_1 = step(&mut host, message, "previous_hash");
+        let res_1 = step(&mut host, message, &level_info());
         let message = next_input(&mut host);
-        let res_2 = step(&mut host, message, "previous_hash");
+        let res_2 = step(&mut host, message, &level_info());
         let message = next_input(&mut host);
-        let res_3 = step(&mut host, message, "previous_hash");
+        let res_3 = step(&mut host, message, &level_info());
 
         assert!(res_1.is_ok());
         assert!(res_2.is_ok());
@@ -298,9 +883,9 @@ mod tests {
         host.as_mut().add_next_inputs(0, inputs);
 
         let message = next_input(&mut host);
-        let res_1 = step(&mut host, message, "previous_hash");
+        let res_1 = step(&mut host, message, &level_info());
         let message = next_input(&mut host);
-        let res_2 = step(&mut host, message, "previous_hash");
+        let res_2 = step(&mut host, message, &level_info());
 
         assert!(res_1.is_ok());
         assert!(res_2.is_ok());
@@ -321,5 +906,1075 @@ mod tests {
             &mut host,
             "/accounts/tz1TGu6TN5GSez2ndXXeDX6LgUDvLzPLqgYV/tweets/writte/0",
         );
+
+        let owner = Key::<PublicKeyHash, _>::new(RefPath::assert_from(b"/tweets/0/owner"))
+            .read(&mut host)
+            .unwrap()
+            .unwrap();
+        assert_eq!(owner.to_string(), "tz1TGu6TN5GSez2ndXXeDX6LgUDvLzPLqgYV");
+    }
+
+    #[test]
+    fn test_receipt_of_created_tweet() {
+        let state = HostState::default();
+        let input = input_1();
+        let inputs = [input.as_slice()].into_iter();
+        let mut host = MockHost::from(state);
+
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = execute(&mut host, &mut TickCounter::default(), &level_info());
+        assert!(res.is_ok());
+
+        let receipt = "/receipts/8d6adf5abde937515ec30373a88059a01daca07bcc9dfd8d164b56ad4a96b6a2";
+        assert_exist(&mut host, &format!("{}/success", receipt));
+        assert_u64(&mut host, &format!("{}/level", receipt), Some(0));
+        assert_u64(&mut host, &format!("{}/operation", receipt), Some(0));
+        assert_u64(&mut host, &format!("{}/tweet_id", receipt), Some(0));
+        assert_not_exists(&mut host, &format!("{}/error", receipt));
+    }
+
+    #[test]
+    fn test_receipt_of_failed_operation() {
+        let state = HostState::default();
+        let input_1 = input_1();
+        let input_2 = input_like_2();
+        let inputs = [input_1.as_slice(), input_2.as_slice()].into_iter();
+        let mut host = MockHost::from(state);
+
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = execute(&mut host, &mut TickCounter::default(), &level_info());
+        assert!(res.is_ok());
+
+        // The like has the nonce 3 instead of 2
+        let receipt = "/receipts/f23c75a26d88846dddaf3ef8a8fab2bcea2060112a11f1dad1f219e5d34e8f20";
+        let code = u64::from(Error::InvalidNonce.code());
+        assert_u64(&mut host, &format!("{}/operation", receipt), Some(1));
+        assert_u64(&mut host, &format!("{}/error", receipt), Some(code));
+        assert_not_exists(&mut host, &format!("{}/tweet_id", receipt));
+    }
+
+    #[test]
+    fn test_receipt_of_malformed_message() {
+        let state = HostState::default();
+        let input = [vec![0x01, MAGIC_BYTE], b"Hello world".to_vec()].concat();
+        let inputs = [input.as_slice()].into_iter();
+        let mut host = MockHost::from(state);
+
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = execute(&mut host, &mut TickCounter::default(), &level_info());
+        assert!(res.is_ok());
+
+        // blake2b of the magic byte followed by "Hello world"
+        let receipt = "/receipts/19ec5aa58def7cb6463c453e6825172486d3704b1c0741aa3c51ee05df065ae9";
+        let json_error = serde_json_wasm::from_str::<u64>("").unwrap_err();
+        let code = u64::from(Error::SerdeJson(json_error).code());
+        assert_u64(&mut host, &format!("{}/error", receipt), Some(code));
+        assert_not_exists(&mut host, &format!("{}/operation", receipt));
+    }
+
+    /// Read the hash of the receipt indexed in an account
+    fn read_account_receipt<Host: RawRollupCore + Runtime>(host: &mut Host, path: &str) -> String {
+        Key::<String, _>::new(RefPath::assert_from(path.as_bytes()))
+            .read(host)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_receipts_of_two_signers() {
+        let state = HostState::default();
+        let input_1 = input_1();
+        let input_2 = input_like_other_account();
+        let input_3 = input_like_third_account();
+        let inputs = [input_1.as_slice(), input_2.as_slice(), input_3.as_slice()].into_iter();
+        let mut host = MockHost::from(state);
+
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = execute(&mut host, &mut TickCounter::default(), &level_info());
+        assert!(res.is_ok());
+
+        // Both accounts have signed the same inner, each of them has its own receipt
+        let receipt_1 = read_account_receipt(
+            &mut host,
+            "/accounts/tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH/receipts/1",
+        );
+        let receipt_2 = read_account_receipt(
+            &mut host,
+            "/accounts/tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT/receipts/1",
+        );
+        assert_ne!(receipt_1, receipt_2);
+        assert_exist(&mut host, &format!("/receipts/{}/success", receipt_1));
+        assert_exist(&mut host, &format!("/receipts/{}/success", receipt_2));
+        assert_u64(&mut host, "/receipts_by_level/0/length", Some(3));
+        assert_u64(&mut host, "/tweets/0/likes", Some(2));
+    }
+
+    #[test]
+    fn test_receipt_of_replayed_message() {
+        let state = HostState::default();
+        let input = input_1();
+        let inputs = [input.as_slice(), input.as_slice()].into_iter();
+        let mut host = MockHost::from(state);
+
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = execute(&mut host, &mut TickCounter::default(), &level_info());
+        assert!(res.is_ok());
+
+        // The replay fails, but the receipt keeps the outcome of the first message
+        let account_receipt = "/accounts/tz1QFD9WqLWZmmAuqnnTPPUjfauitYEWdshv/receipts/1";
+        let receipt = format!("/receipts/{}", read_account_receipt(&mut host, account_receipt));
+        assert_exist(&mut host, &format!("{}/success", receipt));
+        assert_u64(&mut host, &format!("{}/tweet_id", receipt), Some(0));
+        assert_not_exists(&mut host, &format!("{}/error", receipt));
+        assert_u64(&mut host, "/receipts_by_level/0/length", Some(1));
+    }
+
+    #[test]
+    fn test_receipt_of_message_sent_again_after_pause() {
+        let state = HostState::default();
+        let input_1 = input_pause();
+        let input_2 = input_post_other_account();
+        let input_3 = input_unpause();
+        let inputs = [
+            input_1.as_slice(),
+            input_2.as_slice(),
+            input_3.as_slice(),
+            input_2.as_slice(),
+        ]
+        .into_iter();
+        let mut host = MockHost::from(state);
+        install(&mut host);
+
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = execute(&mut host, &mut TickCounter::default(), &level_info());
+        assert!(res.is_ok());
+
+        // The receipt of the rejected message is replaced, without a second entry in the bucket
+        let account_receipt = "/accounts/tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH/receipts/1";
+        let receipt = format!("/receipts/{}", read_account_receipt(&mut host, account_receipt));
+        assert_exist(&mut host, &format!("{}/success", receipt));
+        assert_u64(&mut host, &format!("{}/tweet_id", receipt), Some(0));
+        assert_not_exists(&mut host, &format!("{}/error", receipt));
+        assert_u64(&mut host, "/receipts_by_level/0/length", Some(3));
+    }
+
+    #[test]
+    fn test_prune_receipts() {
+        let state = HostState::default();
+        let input = input_1();
+        let inputs = [input.as_slice()].into_iter();
+        let mut host = MockHost::from(state);
+
+        // Receipts are kept during one level
+        let retention = RefPath::assert_from(b"/config/receipt_retention");
+        host.store_write(&retention, &1_u64.to_be_bytes(), 0)
+            .unwrap();
+
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let () = prune_receipts(&mut host, 0).unwrap();
+        let () = execute(&mut host, &mut TickCounter::default(), &level_info()).unwrap();
+
+        let receipt = "/receipts/8d6adf5abde937515ec30373a88059a01daca07bcc9dfd8d164b56ad4a96b6a2";
+        let account_receipt = "/accounts/tz1QFD9WqLWZmmAuqnnTPPUjfauitYEWdshv/receipts/1";
+        assert_exist(&mut host, receipt);
+        assert_exist(&mut host, account_receipt);
+        assert_u64(&mut host, "/receipts_by_level/0/length", Some(1));
+
+        let () = prune_receipts(&mut host, 1).unwrap();
+        assert_exist(&mut host, receipt);
+
+        let () = prune_receipts(&mut host, 2).unwrap();
+        assert_not_exists(&mut host, receipt);
+        assert_not_exists(&mut host, account_receipt);
+        assert_not_exists(&mut host, "/receipts_by_level/0/length");
+        assert_u64(&mut host, "/gc/receipts/level", Some(1));
+
+        // The levels 1 and 2 have no receipts, the pruning goes past them
+        let () = prune_receipts(&mut host, 4).unwrap();
+        assert_u64(&mut host, "/gc/receipts/level", Some(3));
+    }
+
+    #[test]
+    fn test_prune_receipts_with_max_retention() {
+        let state = HostState::default();
+        let input = input_1();
+        let inputs = [input.as_slice()].into_iter();
+        let mut host = MockHost::from(state);
+
+        // The receipts are kept forever
+        let retention = RefPath::assert_from(b"/config/receipt_retention");
+        host.store_write(&retention, &u64::MAX.to_be_bytes(), 0)
+            .unwrap();
+
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let () = prune_receipts(&mut host, 0).unwrap();
+        let () = execute(&mut host, &mut TickCounter::default(), &level_info()).unwrap();
+
+        let () = prune_receipts(&mut host, u32::MAX).unwrap();
+        assert_exist(
+            &mut host,
+            "/receipts/8d6adf5abde937515ec30373a88059a01daca07bcc9dfd8d164b56ad4a96b6a2",
+        );
+        assert_u64(&mut host, "/gc/receipts/level", Some(0));
+    }
+
+    #[test]
+    fn test_reboot_when_ticks_are_exhausted() {
+        let state = HostState::default();
+        let input = input_1();
+        let inputs = [input.as_slice()].into_iter();
+        let mut host = MockHost::from(state);
+
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        // The budget is exhausted, the message is kept in the inbox
+        let mut ticks = TickCounter::default();
+        ticks.consume(MAX_TICKS);
+        let () = execute(&mut host, &mut ticks, &level_info()).unwrap();
+
+        assert_exist(&mut host, "/kernel/env/reboot");
+        assert_exist(&mut host, "/inbox/resume");
+        assert_not_exists(&mut host, "/tweets/0");
+
+        // The next run processes the remaining messages
+        let () = execute(&mut host, &mut TickCounter::default(), &level_info()).unwrap();
+        assert_exist(&mut host, "/tweets/0");
+    }
+
+    #[test]
+    fn test_collected_tweet_is_frozen() {
+        let state = HostState::default();
+        let input_1 = input_post_other_account();
+        let input_2 = input_collect();
+        let input_3 = input_transfer_collected();
+        let inputs = [input_1.as_slice(), input_2.as_slice(), input_3.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let message = next_input(&mut host);
+        let res_1 = step(&mut host, message, &level_info());
+        let message = next_input(&mut host);
+        let res_2 = step(&mut host, message, &level_info());
+        let message = next_input(&mut host);
+        let res_3 = step(&mut host, message, &level_info());
+
+        assert!(res_1.is_ok());
+        assert!(res_2.is_ok());
+        assert!(matches!(res_3, Err(Error::TransferOfFrozenTweet)));
+
+        assert_exist(&mut host, "/tweets/0/collected_hash");
+        assert_exist(
+            &mut host,
+            "/accounts/tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH/tweets/owned/0",
+        );
+        assert_not_exists(
+            &mut host,
+            "/accounts/tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT/tweets/owned/0",
+        );
+    }
+
+    #[test]
+    fn test_tweet_deposit() {
+        let state = HostState::default();
+        let input_1 = input_post_other_account();
+        let input_2 = input_collect();
+        let input_3 = input_tweet_deposit();
+        let inputs = [
+            input_1.as_slice(),
+            input_2.as_slice(),
+            input_3.as_slice(),
+            input_3.as_slice(),
+        ]
+        .into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let message = next_input(&mut host);
+        let res_1 = step(&mut host, message, &level_info());
+        let message = next_input(&mut host);
+        let res_2 = step(&mut host, message, &level_info());
+        assert!(res_1.is_ok());
+        assert!(res_2.is_ok());
+
+        // The tweet cannot come back before the mint of its token is final
+        let res_3 = match read_input(&mut host) {
+            Ok((_, Input::TweetDeposit(_, deposit))) => {
+                deposit_tweet(&mut host, COLLECT_FINALITY_DELAY - 1, &deposit)
+            }
+            _ => panic!("Expected a tweet deposit"),
+        };
+        assert!(matches!(res_3, Err(Error::TweetNotCollected)));
+
+        let res_4 = match read_input(&mut host) {
+            Ok((_, Input::TweetDeposit(_, deposit))) => {
+                deposit_tweet(&mut host, COLLECT_FINALITY_DELAY, &deposit)
+            }
+            _ => panic!("Expected a tweet deposit"),
+        };
+        assert!(res_4.is_ok());
+
+        let owner = Key::<PublicKeyHash, _>::new(RefPath::assert_from(b"/tweets/0/owner"))
+            .read(&mut host)
+            .unwrap()
+            .unwrap();
+        assert_eq!(owner.to_string(), "tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT");
+        assert_not_exists(&mut host, "/tweets/0/collected_hash");
+        assert_not_exists(
+            &mut host,
+            "/accounts/tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH/collecting/0",
+        );
+        assert_exist(
+            &mut host,
+            "/accounts/tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT/tweets/owned/0",
+        );
+    }
+
+    #[test]
+    fn test_tweet_deposit_to_other_rollup() {
+        let state = HostState::default();
+        let input = to_other_rollup(input_tweet_deposit());
+        let inputs = [input.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = read_input(&mut host);
+        assert!(matches!(res, Err(ReadInputError::NotATzwitterMessage)));
+    }
+
+    #[test]
+    fn test_ticket_deposit() {
+        let state = HostState::default();
+        let input = input_ticket_deposit();
+        let inputs = [input.as_slice(), input.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = execute(&mut host, &mut TickCounter::default(), &level_info());
+        assert!(res.is_ok());
+
+        let ticket = "7dbe280812cf1bf64166e19b997cfbce2e304e992b617a17e7b188dc043e020b";
+        assert_u64(
+            &mut host,
+            &format!(
+                "/accounts/tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT/balances/{}",
+                ticket
+            ),
+            Some(200),
+        );
+        let path = format!("/tickets/{}/ticketer", ticket);
+        let ticketer = Key::<String, _>::new(RefPath::assert_from(path.as_bytes()))
+            .read(&mut host)
+            .unwrap()
+            .unwrap();
+        assert_eq!(ticketer, "KT1UccUhhU6X3rLDtu691TZLHSbKKPxTpNFQ");
+    }
+
+    #[test]
+    fn test_ticket_deposit_to_other_rollup() {
+        let state = HostState::default();
+        let input = to_other_rollup(input_ticket_deposit());
+        let inputs = [input.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = read_input(&mut host);
+        assert!(matches!(res, Err(ReadInputError::NotATzwitterMessage)));
+    }
+
+    #[test]
+    fn test_ticket_deposit_of_unknown_ticketer() {
+        let state = HostState::default();
+        let input = input_ticket_deposit();
+        let inputs = [input.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        whitelisted_ticketer("KT1UccUhhU6X3rLDtu691TZLHSbKKPxTpNFQ")
+            .unwrap()
+            .delete(&mut host)
+            .unwrap();
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = read_input(&mut host);
+        assert!(matches!(res, Err(ReadInputError::Malformed(_, _, Error::UnknownTicketer))));
+    }
+
+    #[test]
+    fn test_withdraw_ticket() {
+        let state = HostState::default();
+        let input_1 = input_ticket_deposit();
+        let input_2 = input_withdraw();
+        let input_3 = input_withdraw_too_much();
+        let inputs = [input_1.as_slice(), input_2.as_slice(), input_3.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        match read_input(&mut host).unwrap() {
+            (_, Input::TicketDeposit(_, deposit)) => {
+                deposit_ticket(&mut host, &deposit).unwrap()
+            }
+            _ => panic!("Expected a ticket deposit"),
+        }
+
+        let message = next_input(&mut host);
+        let res_1 = step(&mut host, message, &level_info());
+        let message = next_input(&mut host);
+        let res_2 = step(&mut host, message, &level_info());
+
+        assert!(res_1.is_ok());
+        assert!(matches!(res_2, Err(Error::InsufficientBalance)));
+        assert_u64(
+            &mut host,
+            "/accounts/tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT/balances/7dbe280812cf1bf64166e19b997cfbce2e304e992b617a17e7b188dc043e020b",
+            Some(60),
+        );
+    }
+
+    #[test]
+    fn test_tip_tweet() {
+        let state = HostState::default();
+        let input_1 = input_post_other_account();
+        let input_2 = input_ticket_deposit();
+        let input_3 = input_tip();
+        let input_4 = input_tip_missing_tweet();
+        let inputs = [
+            input_1.as_slice(),
+            input_2.as_slice(),
+            input_3.as_slice(),
+            input_4.as_slice(),
+        ]
+        .into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let message = next_input(&mut host);
+        let res_1 = step(&mut host, message, &level_info());
+        match read_input(&mut host).unwrap() {
+            (_, Input::TicketDeposit(_, deposit)) => {
+                deposit_ticket(&mut host, &deposit).unwrap()
+            }
+            _ => panic!("Expected a ticket deposit"),
+        }
+        let message = next_input(&mut host);
+        let res_2 = step(&mut host, message, &level_info());
+        let message = next_input(&mut host);
+        let res_3 = step(&mut host, message, &level_info());
+
+        assert!(res_1.is_ok());
+        assert!(res_2.is_ok());
+        assert!(matches!(res_3, Err(Error::TweetNotFound)));
+
+        let ticket = "7dbe280812cf1bf64166e19b997cfbce2e304e992b617a17e7b188dc043e020b";
+        let balance = |account: &str| format!("/accounts/{}/balances/{}", account, ticket);
+        assert_u64(&mut host, &balance("tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT"), Some(70));
+        assert_u64(&mut host, &balance("tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH"), Some(30));
+        assert_u64(&mut host, &format!("/tweets/0/tips/{}", ticket), Some(30));
+        assert_u64(
+            &mut host,
+            &format!("/accounts/tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH/tips/{}", ticket),
+            Some(30),
+        );
+    }
+
+    #[test]
+    fn test_buy_tweet() {
+        let state = HostState::default();
+        let input_1 = input_post_other_account();
+        let input_2 = input_list();
+        let input_3 = input_ticket_deposit();
+        let input_4 = input_buy();
+        let inputs = [
+            input_1.as_slice(),
+            input_2.as_slice(),
+            input_3.as_slice(),
+            input_4.as_slice(),
+        ]
+        .into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let message = next_input(&mut host);
+        let res_1 = step(&mut host, message, &level_info());
+        let message = next_input(&mut host);
+        let res_2 = step(&mut host, message, &level_info());
+        assert_u64(&mut host, "/market/0/price", Some(50));
+
+        match read_input(&mut host).unwrap() {
+            (_, Input::TicketDeposit(_, deposit)) => {
+                deposit_ticket(&mut host, &deposit).unwrap()
+            }
+            _ => panic!("Expected a ticket deposit"),
+        }
+        let message = next_input(&mut host);
+        let res_3 = step(&mut host, message, &level_info());
+
+        assert!(res_1.is_ok());
+        assert!(res_2.is_ok());
+        assert!(res_3.is_ok());
+
+        let owner = read_owner(&mut host, &0).unwrap().unwrap();
+        assert_eq!(owner.to_string(), "tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT");
+        assert_not_exists(&mut host, "/market/0");
+
+        let ticket = "7dbe280812cf1bf64166e19b997cfbce2e304e992b617a17e7b188dc043e020b";
+        let balance = |account: &str| format!("/accounts/{}/balances/{}", account, ticket);
+        assert_u64(&mut host, &balance("tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT"), Some(50));
+        assert_u64(&mut host, &balance("tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH"), Some(50));
+    }
+
+    #[test]
+    fn test_transfer_cancels_listing() {
+        let state = HostState::default();
+        let input_1 = input_post_other_account();
+        let input_2 = input_list();
+        let input_3 = input_transfer_collected();
+        let inputs = [input_1.as_slice(), input_2.as_slice(), input_3.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        for _ in 0..3 {
+            let message = next_input(&mut host);
+            let res = step(&mut host, message, &level_info());
+            assert!(res.is_ok());
+        }
+
+        assert_not_exists(&mut host, "/market/0");
+    }
+
+    #[test]
+    fn test_auction() {
+        let state = HostState::default();
+        let input_1 = input_post_other_account();
+        let input_2 = input_open_auction();
+        let input_3 = input_ticket_deposit();
+        let input_4 = input_bid();
+        let input_5 = input_bid_too_low();
+        let input_6 = input_outbid();
+        let inputs = [
+            input_1.as_slice(),
+            input_2.as_slice(),
+            input_3.as_slice(),
+            input_4.as_slice(),
+            input_5.as_slice(),
+            input_6.as_slice(),
+        ]
+        .into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = execute(&mut host, &mut TickCounter::default(), &level_info());
+        assert!(res.is_ok());
+
+        let ticket = "7dbe280812cf1bf64166e19b997cfbce2e304e992b617a17e7b188dc043e020b";
+        let balance = |account: &str| format!("/accounts/{}/balances/{}", account, ticket);
+        // The first bid has been refunded, the second one is escrowed
+        assert_u64(&mut host, &balance("tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT"), Some(60));
+        assert_u64(&mut host, "/auctions/0/bid", Some(40));
+        assert_exist(&mut host, "/auctions_by_level/0/0");
+
+        // The auction is settled during the next level
+        let next_level = LevelInfo {
+            level: 1,
+            previous_hash: "previous_hash".to_string(),
+        };
+        let res = execute(&mut host, &mut TickCounter::default(), &next_level);
+        assert!(res.is_ok());
+
+        let owner = read_owner(&mut host, &0).unwrap().unwrap();
+        assert_eq!(owner.to_string(), "tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT");
+        assert_u64(&mut host, &balance("tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH"), Some(40));
+        assert_not_exists(&mut host, "/auctions/0");
+        assert_not_exists(&mut host, "/auctions_by_level/0");
+    }
+
+    #[test]
+    fn test_auction_too_long() {
+        let state = HostState::default();
+        let input_1 = input_post_other_account();
+        let input_2 = input_open_auction_too_long();
+        let inputs = [input_1.as_slice(), input_2.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let message = next_input(&mut host);
+        let res_1 = step(&mut host, message, &level_info());
+        let message = next_input(&mut host);
+        let res_2 = step(&mut host, message, &level_info());
+
+        assert!(res_1.is_ok());
+        assert!(matches!(res_2, Err(Error::AuctionTooLong)));
+        assert_not_exists(&mut host, "/auctions/0");
+    }
+
+    #[test]
+    fn test_failed_auction_settlement() {
+        let state = HostState::default();
+        let input_1 = input_post_other_account();
+        let input_2 = input_open_auction();
+        let input_3 = input_ticket_deposit();
+        let input_4 = input_bid();
+        let inputs = [
+            input_1.as_slice(),
+            input_2.as_slice(),
+            input_3.as_slice(),
+            input_4.as_slice(),
+        ]
+        .into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = execute(&mut host, &mut TickCounter::default(), &level_info());
+        assert!(res.is_ok());
+
+        // The balance of the seller overflows when the bid is paid
+        let ticket = "7dbe280812cf1bf64166e19b997cfbce2e304e992b617a17e7b188dc043e020b";
+        let balance = |account: &str| format!("/accounts/{}/balances/{}", account, ticket);
+        let seller_balance = balance("tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH");
+        Key::<u64, _>::new(RefPath::assert_from(seller_balance.as_bytes()))
+            .write(&mut host, &u64::MAX)
+            .unwrap();
+
+        let next_level = LevelInfo {
+            level: 1,
+            previous_hash: "previous_hash".to_string(),
+        };
+        let res = execute(&mut host, &mut TickCounter::default(), &next_level);
+        assert!(res.is_ok());
+
+        // The auction is cancelled, the seller keeps the tweet and the bid is refunded
+        let owner = read_owner(&mut host, &0).unwrap().unwrap();
+        assert_eq!(owner.to_string(), "tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH");
+        assert_u64(&mut host, &seller_balance, Some(u64::MAX));
+        assert_u64(&mut host, &balance("tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT"), Some(100));
+        assert_not_exists(&mut host, "/auctions/0");
+        assert_not_exists(&mut host, "/auctions_by_level/0");
+    }
+
+    #[test]
+    fn test_pause_freezes_auctions() {
+        let state = HostState::default();
+        let input_1 = input_post_other_account();
+        let input_2 = input_open_auction();
+        let input_3 = input_ticket_deposit();
+        let input_4 = input_bid();
+        let input_5 = input_pause();
+        let inputs = [
+            input_1.as_slice(),
+            input_2.as_slice(),
+            input_3.as_slice(),
+            input_4.as_slice(),
+            input_5.as_slice(),
+        ]
+        .into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = execute(&mut host, &mut TickCounter::default(), &level_info());
+        assert!(res.is_ok());
+        assert_exist(&mut host, "/config/paused");
+
+        // The auction is not settled while the rollup is paused
+        let level_1 = LevelInfo {
+            level: 1,
+            previous_hash: "previous_hash".to_string(),
+        };
+        let res = execute(&mut host, &mut TickCounter::default(), &level_1);
+        assert!(res.is_ok());
+        assert_exist(&mut host, "/auctions/0");
+
+        // It is settled once the rollup is unpaused
+        set_paused(&mut host, false).unwrap();
+        let level_2 = LevelInfo {
+            level: 2,
+            previous_hash: "previous_hash".to_string(),
+        };
+        let res = execute(&mut host, &mut TickCounter::default(), &level_2);
+        assert!(res.is_ok());
+
+        let owner = read_owner(&mut host, &0).unwrap().unwrap();
+        assert_eq!(owner.to_string(), "tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT");
+        assert_not_exists(&mut host, "/auctions/0");
+        assert_not_exists(&mut host, "/auctions_by_level/0");
+        assert_u64(&mut host, "/gc/auctions/level", Some(2));
+    }
+
+    #[test]
+    fn test_royalties() {
+        let state = HostState::default();
+        let input_1 = input_post_with_royalties();
+        let input_2 = input_transfer_to_reseller();
+        let input_3 = input_resale();
+        let input_4 = input_ticket_deposit();
+        let input_5 = input_buy();
+        let inputs = [
+            input_1.as_slice(),
+            input_2.as_slice(),
+            input_3.as_slice(),
+            input_4.as_slice(),
+            input_5.as_slice(),
+        ]
+        .into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = execute(&mut host, &mut TickCounter::default(), &level_info());
+        assert!(res.is_ok());
+        assert_u64(&mut host, "/tweets/0/royalties", Some(10));
+
+        let owner = read_owner(&mut host, &0).unwrap().unwrap();
+        assert_eq!(owner.to_string(), "tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT");
+
+        // The author receives 10% of the price paid to the reseller
+        let ticket = "7dbe280812cf1bf64166e19b997cfbce2e304e992b617a17e7b188dc043e020b";
+        let balance = |account: &str| format!("/accounts/{}/balances/{}", account, ticket);
+        assert_u64(&mut host, &balance("tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT"), Some(50));
+        assert_u64(&mut host, &balance("tz1XW83nyuhuQFyVMSRu1G1S2JhqGdES7qou"), Some(45));
+        assert_u64(&mut host, &balance("tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH"), Some(5));
+    }
+
+    #[test]
+    fn test_set_l1_contract() {
+        let state = HostState::default();
+        let input_1 = input_set_l1_contract();
+        let input_2 = input_set_l1_contract();
+        let input_3 = input_set_l1_contract_not_admin();
+        let input_4 = input_tweet_deposit();
+        let inputs = [
+            input_1.as_slice(),
+            input_2.as_slice(),
+            input_3.as_slice(),
+            input_4.as_slice(),
+        ]
+        .into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let message = next_admin_input(&mut host);
+        let res_1 = admin_step(&mut host, message);
+        let message = next_admin_input(&mut host);
+        let res_2 = admin_step(&mut host, message);
+        let message = next_admin_input(&mut host);
+        let res_3 = admin_step(&mut host, message);
+
+        assert!(res_1.is_ok());
+        assert!(matches!(res_2, Err(Error::InvalidNonce)));
+        assert!(matches!(res_3, Err(Error::NotAdmin)));
+        assert_u64(&mut host, "/admin/nonce", Some(1));
+        assert_exist(&mut host, "/admin/receipts/1");
+
+        let l1_contract = L1_CONTRACT.read(&mut host).unwrap().unwrap();
+        assert_eq!(l1_contract, "KT1BN5u3CeRzVC7UaJYF4ADYtiHFHzqwC8dV");
+
+        // The deposits of the previous contract are ignored
+        let res_4 = read_input(&mut host);
+        assert!(matches!(res_4, Err(ReadInputError::NotATzwitterMessage)));
+    }
+
+    #[test]
+    fn test_moderator() {
+        let state = HostState::default();
+        let input_1 = input_add_moderator();
+        let input_2 = input_post_other_account();
+        let input_3 = input_moderate();
+        let inputs = [input_1.as_slice(), input_2.as_slice(), input_3.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let res = execute(&mut host, &mut TickCounter::default(), &level_info());
+        assert!(res.is_ok());
+
+        // The moderator has deleted a tweet they do not own
+        assert_exist(&mut host, "/moderators/tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT");
+        assert_not_exists(&mut host, "/tweets/0");
+        assert_not_exists(
+            &mut host,
+            "/accounts/tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH/tweets/owned/0",
+        );
+        assert_not_exists(
+            &mut host,
+            "/accounts/tz1c8PEDNfj6UxoQM2XCyfTHM5KbGGgoqDrH/tweets/written/0",
+        );
+    }
+
+    #[test]
+    fn test_delete_not_moderator() {
+        let state = HostState::default();
+        let input_1 = input_post_other_account();
+        let input_2 = input_delete_not_moderator();
+        let inputs = [input_1.as_slice(), input_2.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let message = next_input(&mut host);
+        let res_1 = step(&mut host, message, &level_info());
+        let message = next_input(&mut host);
+        let res_2 = step(&mut host, message, &level_info());
+
+        // Even the author cannot delete their tweet
+        assert!(res_1.is_ok());
+        assert!(matches!(res_2, Err(Error::NotModerator)));
+        assert_exist(&mut host, "/tweets/0/record");
+    }
+
+    #[test]
+    fn test_governance_to_other_rollup() {
+        let state = HostState::default();
+        let input = to_other_rollup(input_add_moderator());
+        let inputs = [input.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        // The operation is addressed to another rollup, it has no receipt
+        let res = execute(&mut host, &mut TickCounter::default(), &level_info());
+        assert!(res.is_ok());
+        assert_not_exists(&mut host, "/moderators/tz1RmNmuNm9ygsA17RBzX38GJWG3Bex5U9UT");
+        assert_not_exists(&mut host, "/receipts_by_level/0/length");
+    }
+
+    #[test]
+    fn test_pause() {
+        let state = HostState::default();
+        let input_1 = input_pause();
+        let input_2 = input_post_other_account();
+        let input_3 = input_unpause();
+        let input_4 = input_post_other_account();
+        let inputs = [
+            input_1.as_slice(),
+            input_2.as_slice(),
+            input_3.as_slice(),
+            input_4.as_slice(),
+        ]
+        .into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let message = next_admin_input(&mut host);
+        let res_1 = admin_step(&mut host, message);
+        let message = next_input(&mut host);
+        let res_2 = step(&mut host, message, &level_info());
+        assert!(res_1.is_ok());
+        assert!(matches!(res_2, Err(Error::Paused)));
+        assert_exist(&mut host, "/config/paused");
+        assert_not_exists(&mut host, "/tweets/0");
+
+        // The nonce has not been consumed, so the same message is accepted once unpaused
+        let message = next_admin_input(&mut host);
+        let res_3 = admin_step(&mut host, message);
+        let message = next_input(&mut host);
+        let res_4 = step(&mut host, message, &level_info());
+        assert!(res_3.is_ok());
+        assert!(res_4.is_ok());
+        assert_not_exists(&mut host, "/config/paused");
+        assert_exist(&mut host, "/tweets/0");
+    }
+
+    #[test]
+    fn test_upgrade_kernel() {
+        let kernel = b"\0asm\x01\0\0\0";
+        let mut state = HostState::default();
+        let root_hash = prepare_preimages(kernel, |_, page| {
+            state.set_preimage(page);
+        })
+        .unwrap();
+        assert_eq!(
+            hex::encode(root_hash.as_ref()),
+            "007d218ff04dbbada13a87dadd1686fffa3ac617be7804e1503e4b0d94cea5a1d5"
+        );
+        let input = input_upgrade_kernel();
+        let inputs = [input.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        install(&mut host);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        // The upgrade is only scheduled by the admin
+        let message = next_admin_input(&mut host);
+        let res = admin_step(&mut host, message);
+        assert!(res.is_ok());
+        assert_exist(&mut host, "/upgrade/root_hash");
+        assert_not_exists(&mut host, "/kernel/boot.wasm");
+
+        // And installed at the beginning of the next run
+        let res = install_kernel_upgrade(&mut host, MAX_KERNEL_PAGES_PER_RUN);
+        assert!(matches!(res, Ok(1)));
+        assert_not_exists(&mut host, "/upgrade");
+        let path = RefPath::assert_from(b"/kernel/boot.wasm");
+        let boot = host.store_read(&path, 0, kernel.len()).unwrap();
+        assert_eq!(boot, kernel);
+
+        // There is nothing left to install
+        let res = install_kernel_upgrade(&mut host, MAX_KERNEL_PAGES_PER_RUN);
+        assert!(matches!(res, Ok(0)));
+    }
+
+    #[test]
+    fn test_upgrade_kernel_across_runs() {
+        let mut kernel = b"\0asm\x01\0\0\0".to_vec();
+        kernel.extend_from_slice(&[1; 10_000]);
+        let mut state = HostState::default();
+        let root_hash = prepare_preimages(&kernel, |_, page| {
+            state.set_preimage(page);
+        })
+        .unwrap();
+
+        let mut host = MockHost::from(state);
+        let root_hash = hex::encode(root_hash.as_ref());
+        let res = schedule_kernel_upgrade(&mut host, root_hash);
+        assert!(res.is_ok());
+
+        // The kernel is made of a page of hashes and 3 pages of content, one is revealed per run
+        for _ in 0..3 {
+            let res = install_kernel_upgrade(&mut host, 1);
+            assert!(matches!(res, Ok(1)));
+            assert_exist(&mut host, "/upgrade/root_hash");
+            assert_not_exists(&mut host, "/kernel/boot.wasm");
+        }
+
+        let res = install_kernel_upgrade(&mut host, 1);
+        assert!(matches!(res, Ok(1)));
+        assert_not_exists(&mut host, "/upgrade");
+        let path = RefPath::assert_from(b"/kernel/boot.wasm");
+        let boot = Storage::read(&mut host, &path).unwrap();
+        assert_eq!(boot, Some(kernel));
+    }
+
+    #[test]
+    fn test_long_tweet() {
+        let body = "Hello world! ".repeat(400);
+        let too_long = "a".repeat(MAX_LONG_TWEET_SIZE + 1);
+        let mut state = HostState::default();
+        let body_hash = prepare_preimages(body.as_bytes(), |_, page| {
+            state.set_preimage(page);
+        })
+        .unwrap();
+        let too_long_hash = prepare_preimages(too_long.as_bytes(), |_, page| {
+            state.set_preimage(page);
+        })
+        .unwrap();
+        assert_eq!(
+            hex::encode(body_hash.as_ref()),
+            "0099d0c4507ac0e1a083871e6439458038f6e69c97847191340a34a0030564b976"
+        );
+        assert_eq!(
+            hex::encode(too_long_hash.as_ref()),
+            "0043a8c1112a9128fc3509625c0f989d6d69c28c32c57b2157a49275462fe7cde1"
+        );
+
+        let input_1 = input_post_long_tweet();
+        let input_2 = input_post_too_long_tweet();
+        let inputs = [input_1.as_slice(), input_2.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let message = next_input(&mut host);
+        let res_1 = step(&mut host, message, &level_info());
+        let message = next_input(&mut host);
+        let res_2 = step(&mut host, message, &level_info());
+
+        assert!(res_1.is_ok());
+        assert!(matches!(res_2, Err(Error::LongTweetTooLarge)));
+        let path = RefPath::assert_from(b"/tweets/0/body");
+        let body = Key::<String, _>::new(path).read(&mut host).unwrap();
+        assert_eq!(body, Some(hex::encode(body_hash.as_ref())));
+        assert_not_exists(&mut host, "/tweets/1");
+    }
+
+    #[test]
+    fn test_media() {
+        let state = HostState::default();
+        let input_1 = input_post_with_media();
+        let input_2 = input_post_with_invalid_media();
+        let inputs = [input_1.as_slice(), input_2.as_slice()].into_iter();
+
+        let mut host = MockHost::from(state);
+        host.as_mut().set_ready_for_input(0);
+        host.as_mut().add_next_inputs(0, inputs);
+
+        let message = next_input(&mut host);
+        let res_1 = step(&mut host, message, &level_info());
+        let message = next_input(&mut host);
+        let res_2 = step(&mut host, message, &level_info());
+
+        assert!(res_1.is_ok());
+        assert!(matches!(res_2, Err(Error::InvalidMedia)));
+        assert_exist(&mut host, "/tweets/0/media/0");
+        assert_exist(&mut host, "/tweets/0/media/1");
+        assert_not_exists(&mut host, "/tweets/0/media/2");
+        assert_not_exists(&mut host, "/tweets/1");
     }
 }
@@ -1,62 +1,248 @@
 use crate::{
-    constants::{L1_TOKEN_CONTRACT_ADDRESS, L1_TOKEN_CONTRACT_ENTRYPOINT, MAGIC_BYTE},
+    constants::{
+        ADMIN_MAGIC_BYTE, MAGIC_BYTE, MAX_AUCTIONS_PER_LEVEL, MAX_AUCTION_DURATION, MAX_DAC_LEVELS,
+        MAX_KERNEL_PAGES, MAX_KERNEL_SIZE, MAX_LONG_TWEET_SIZE, MAX_MEDIA_PER_TWEET,
+        MAX_PRUNED_RECEIPTS_PER_LEVEL, MAX_ROYALTIES,
+    },
     core::{
         account::Account,
-        message::{Content, Inner, PostTweet, Transfer},
+        admin::{AdminContent, AdminMessage, SetL1Contract},
+        level::LevelInfo,
+        market::{Auction, BestBid, Listing},
+        media::Media,
+        message::{
+            Bid, Content, Inner, OpenAuction, PostLongTweet, PostTweet, Sale, Tip, Transfer,
+            Withdraw,
+        },
         nonce::Nonce,
-        tweet::Tweet,
+        tweet::{Tweet, TweetState},
     },
     storage::{
-        self, add_collecting_tweet_to_account, add_owned_tweet_to_account,
-        add_written_tweet_to_account, increment_tweet_counter, is_liked, is_not_collected,
-        is_owner, read_tweet, set_collected_block, set_like_flag, store_tweet,
+        self, add_auction_to_level, add_collecting_tweet_to_account, add_tip,
+        add_written_tweet_to_account, credit_balance, debit_balance, delete_auction,
+        delete_collected_block, delete_level_auctions, delete_level_receipt, delete_level_receipts,
+        delete_listing, delete_upgrade, increment_likes, increment_tweet_counter, is_auctioned,
+        is_liked, is_moderator, is_owner, is_whitelisted_ticketer, read_auction, read_governance,
+        read_l1_contract, read_l1_entrypoint, read_level_auction, read_level_auctions_length,
+        read_level_receipts_length, read_likes, read_listing, read_media, read_owner,
+        read_pending_upgrade, read_pruning_cursor, read_receipt_retention, read_rollup_address,
+        read_royalties, read_settlement_cursor, read_ticketer, read_tweet, read_tweet_state,
+        read_upgrade_progress, remove_collecting_tweet_from_account, set_body, set_collected_block,
+        set_like_flag, set_owner, set_royalties, set_tweet_state, store_auction, store_l1_contract,
+        store_listing, store_media, store_pending_upgrade, store_pruning_cursor,
+        store_rollup_address, store_settlement_cursor, store_ticketer, store_tweet,
+        store_upgrade_progress, Storage, Transaction, KERNEL_BOOT, KERNEL_STAGING, UPGRADE_STACK,
     },
 };
+use crypto::hash::SmartRollupHash;
 use host::{
-    rollup_core::{RawRollupCore, MAX_INPUT_MESSAGE_SIZE},
+    rollup_core::{RawRollupCore, MAX_FILE_CHUNK_SIZE, MAX_INPUT_MESSAGE_SIZE, PREIMAGE_HASH_SIZE},
     runtime::Runtime,
 };
 use num_bigint::ToBigInt;
 use tezos_data_encoding::{enc::BinWriter, types::Zarith};
 
+use crate::core::deposit::{TicketDeposit, TweetDeposit};
 use crate::core::error::*;
-use crate::core::message::Message;
-use tezos_rollup_encoding::{contract::Contract, inbox::InboxMessage, michelson::*};
+use crate::core::hash::Blake2b;
+use crate::core::message::{InboxPosition, Input, Message};
+use crate::core::public_key_hash::PublicKeyHash;
+use serde::de::DeserializeOwned;
+use tezos_rollup_encoding::{
+    contract::Contract,
+    dac::{
+        fetch_page_raw, reveal_loop, SlicePage, V0SliceContentPage, V0SliceHashPage, MAX_PAGE_SIZE,
+    },
+    inbox::{InboxMessage, InternalInboxMessage},
+    michelson::{
+        ticket::{TicketHash, UnitTicket},
+        *,
+    },
+    smart_rollup::SmartRollupAddress,
+};
 use tezos_rollup_encoding::{entrypoint::Entrypoint, outbox::*};
 
+/// Error of the reveal loop when the body of a long tweet exceeds MAX_LONG_TWEET_SIZE
+const LONG_TWEET_TOO_LARGE: &str = "The long tweet is too large";
+
+/// First bytes of a wasm module
+const WASM_MAGIC: &[u8] = b"\0asm";
+
+/// Payload of the transfers sent by the layer 1 token contract to the rollup
+/// (pair (nat %id) (address %receiver))
+type TweetDepositPayload = MichelsonPair<MichelsonInt, MichelsonContract>;
+
+/// Payload of the transfers sent by the ticketers to the rollup
+/// (pair (address %receiver) (ticket %ticket unit))
+type TicketDepositPayload = MichelsonPair<MichelsonContract, UnitTicket>;
+
+/// Payload of the transfers sent by the governance contract to the rollup
+/// The bytes are the JSON encoding of an admin operation
+type GovernancePayload = MichelsonBytes;
+
+/// Type of the rollup
+/// (or (or (bytes %governance) (pair %ticket address (ticket unit))) (pair %tweet nat address))
+type RollupParameter =
+    MichelsonOr<MichelsonOr<GovernancePayload, TicketDepositPayload>, TweetDepositPayload>;
+
 /// Read a message from the inbox
 ///
-/// It will only read messages External Messages with the MAGIC_BYTE
+/// It will only read External Messages with the MAGIC_BYTE or the ADMIN_MAGIC_BYTE
+/// And Internal Transfers sent by the layer 1 token contract, by a whitelisted ticketer
+/// Or by the governance contract
+/// The message is returned with its position in the inbox
+/// If the message cannot be decoded, the hash of its payload is returned with the error
 /// Benchmark: 2_000_000 ticks (processing an inbox with only one message)
 pub fn read_input<Host: RawRollupCore>(
     host: &mut Host,
-) -> std::result::Result<Message, ReadInputError> {
+) -> std::result::Result<(InboxPosition, Input), ReadInputError> {
     let input = host
         .read_input(MAX_INPUT_MESSAGE_SIZE)
         .map_err(|err| ReadInputError::Runtime(err))?;
     match input {
         None => Err(ReadInputError::EndOfInbox),
         Some(message) => {
+            let position = InboxPosition {
+                level: message.level,
+                index: message.id,
+            };
             let data = message.as_ref();
             match data {
-                [0x01, MAGIC_BYTE, ..] => {
-                    let bytes = data.iter().skip(2).copied().collect();
-                    let str = String::from_utf8(bytes)
-                        .map_err(|err| ReadInputError::FromUtf8Error(err))?;
-                    serde_json_wasm::from_str(&str).map_err(|err| ReadInputError::SerdeJson(err))
-                }
+                [0x00, ..] => read_deposit(host, data)
+                    .map_err(|err| match err {
+                        Some(err) => {
+                            let hash = Blake2b::from(data);
+                            ReadInputError::Malformed(position, hash, err)
+                        }
+                        None => ReadInputError::NotATzwitterMessage,
+                    })
+                    .map(|input| (position, input)),
+                [0x01, MAGIC_BYTE, ..] => read_external(position, data)
+                    .map(|message| (position, Input::Message(message))),
+                [0x01, ADMIN_MAGIC_BYTE, ..] => read_external(position, data)
+                    .map(|message| (position, Input::AdminMessage(message))),
                 _ => Err(ReadInputError::NotATzwitterMessage),
             }
         }
     }
 }
 
-/// Returns the hash of the previous block
+/// Decode the JSON payload of an external message, which follows its magic byte
+fn read_external<T: DeserializeOwned>(
+    position: InboxPosition,
+    data: &[u8],
+) -> std::result::Result<T, ReadInputError> {
+    // The receipt of a malformed message is identified by the hash of the payload
+    let malformed = |err: Error| {
+        let hash = Blake2b::from(&data[1..]);
+        ReadInputError::Malformed(position, hash, err)
+    };
+    let bytes = data.iter().skip(2).copied().collect();
+    let str = String::from_utf8(bytes).map_err(|err| malformed(Error::from(err)))?;
+    serde_json_wasm::from_str(&str).map_err(|err| malformed(Error::from(err)))
+}
+
+/// Read the address of this rollup
+///
+/// It is revealed from the metadata of the rollup the first time, and then read from /config
+fn rollup_address<Host: RawRollupCore + Runtime>(host: &mut Host) -> Result<String> {
+    if let Some(rollup_address) = read_rollup_address(host)? {
+        return Ok(rollup_address);
+    }
+    let metadata = host.reveal_metadata().map_err(Error::from)?;
+    let hash = SmartRollupHash(metadata.raw_rollup_address.to_vec());
+    let rollup_address = SmartRollupAddress::new(hash).to_b58check();
+    let () = store_rollup_address(host, &rollup_address)?;
+    Ok(rollup_address)
+}
+
+/// Decode an internal transfer sent to the rollup
+///
+/// Returns an error without cause if the message is not a transfer from a known contract
+/// Or if it is sent to another rollup
+/// The deposit is identified by the hash of the whole inbox message
+fn read_deposit<Host: RawRollupCore>(
+    host: &mut Host,
+    data: &[u8],
+) -> std::result::Result<Input, Option<Error>> {
+    let (_, message) = InboxMessage::<RollupParameter>::parse(data).map_err(|_| None)?;
+    let transfer = match message {
+        InboxMessage::Internal(InternalInboxMessage::Transfer(transfer)) => transfer,
+        _ => return Err(None),
+    };
+    // The inbox is shared by all the rollups
+    let rollup_address = rollup_address(host).map_err(Some)?;
+    if transfer.destination.to_b58check() != rollup_address {
+        return Err(None);
+    }
+    let sender = transfer.sender.to_b58check();
+    let hash = Blake2b::from(data);
+
+    match transfer.payload {
+        MichelsonOr::Left(MichelsonOr::Left(MichelsonBytes(bytes))) => {
+            let governance = read_governance(host).map_err(Some)?;
+            if governance.as_ref() != Some(&transfer.sender) {
+                return Err(None);
+            }
+            let content =
+                serde_json_wasm::from_slice(&bytes).map_err(|err| Some(Error::from(err)))?;
+            Ok(Input::Governance(hash, content))
+        }
+        MichelsonOr::Left(MichelsonOr::Right(payload)) => {
+            let MichelsonPair(MichelsonContract(receiver), ticket) = payload;
+            // The tickets have to be sent by the ticketer who has created them
+            let ticketer = match ticket.creator() {
+                MichelsonContract(Contract::Originated(ticketer)) => ticketer.to_b58check(),
+                MichelsonContract(Contract::Implicit(_)) => {
+                    return Err(Some(Error::UnknownTicketer))
+                }
+            };
+            let is_whitelisted = is_whitelisted_ticketer(host, &ticketer).map_err(Some)?;
+            if ticketer != sender || !is_whitelisted {
+                return Err(Some(Error::UnknownTicketer));
+            }
+            let amount = ticket
+                .amount_as::<u64, _>()
+                .map_err(|_| Some(Error::BigIntError))?;
+            let receiver = read_receiver(receiver).map_err(Some)?;
+            let ticket = ticket.hash().map_err(|err| Some(Error::from(err)))?;
+            Ok(Input::TicketDeposit(
+                hash,
+                TicketDeposit {
+                    ticket,
+                    ticketer,
+                    amount,
+                    receiver,
+                },
+            ))
+        }
+        MichelsonOr::Right(payload) => {
+            let l1_contract = read_l1_contract(host).map_err(Some)?;
+            if l1_contract.as_deref() != Some(sender.as_str()) {
+                return Err(None);
+            }
+            let MichelsonPair(MichelsonInt(Zarith(tweet_id)), MichelsonContract(receiver)) =
+                payload;
+            let tweet_id = u64::try_from(&tweet_id).map_err(|_| Some(Error::BigIntError))?;
+            let receiver = read_receiver(receiver).map_err(Some)?;
+            Ok(Input::TweetDeposit(hash, TweetDeposit { tweet_id, receiver }))
+        }
+    }
+}
+
+/// Only tz1 accounts can receive something on the rollup
+fn read_receiver(receiver: Contract) -> Result<PublicKeyHash> {
+    match receiver {
+        Contract::Implicit(receiver) => PublicKeyHash::from_b58(&receiver.to_b58check())
+            .map_err(|_| Error::UnsupportedReceiver),
+        Contract::Originated(_) => Err(Error::UnsupportedReceiver),
+    }
+}
+
+/// Returns the current level and the hash of the previous block
 /// /!\ /!\ This function should be call one time BEFORE the read_input function (see above)
 /// Because it will read the first 2 messages of the inbox
-/// TODO: how to have the current level of the block?
-/// It should be better if this function returns the current level of the block
-pub fn get_previous_block_hash<Host: RawRollupCore + Runtime>(host: &mut Host) -> Result<String> {
+pub fn read_level_info<Host: RawRollupCore + Runtime>(host: &mut Host) -> Result<LevelInfo> {
     // It ignores the StartOfLevel
     let _ = host
         .read_input(MAX_INPUT_MESSAGE_SIZE)
@@ -70,6 +256,7 @@ pub fn get_previous_block_hash<Host: RawRollupCore + Runtime>(host: &mut Host) -
     // And then extract the precessor hash as a string (which not the best type)
 
     let input = input.ok_or(Error::NotInfoPerLevelMsg)?;
+    let level = input.level;
     let data = input.as_ref();
     let msg = InboxMessage::<MichelsonUnit>::parse(data)
         .map_err(|_| Error::NotInfoPerLevelMsg)?
@@ -78,14 +265,52 @@ pub fn get_previous_block_hash<Host: RawRollupCore + Runtime>(host: &mut Host) -
     match msg {
         InboxMessage::External(_) => Err(Error::NotInfoPerLevelMsg),
         InboxMessage::Internal(msg) => match msg {
-            tezos_rollup_encoding::inbox::InternalInboxMessage::InfoPerLevel(info) => {
-                Ok(info.predecessor.to_base58_check())
-            }
+            InternalInboxMessage::InfoPerLevel(info) => Ok(LevelInfo {
+                level,
+                previous_hash: info.predecessor.to_base58_check(),
+            }),
             _ => Err(Error::NotInfoPerLevelMsg),
         },
     }
 }
 
+/// Delete the receipts that are older than the retention period
+///
+/// Receipts are deleted in the order they have been stored, level by level
+/// Their entries in the accounts and in the admin receipts are deleted with them
+/// At most MAX_PRUNED_RECEIPTS_PER_LEVEL receipts or levels are visited per call
+/// The remaining receipts are deleted during the next levels
+pub fn prune_receipts<Host: RawRollupCore + Runtime>(host: &mut Host, level: u32) -> Result<()> {
+    let current_level = u64::from(level);
+    let retention = read_receipt_retention(host)?;
+
+    // There are no receipts older than the first pruning
+    let (mut level, mut index) = read_pruning_cursor(host)?.unwrap_or((current_level, 0));
+
+    let mut budget = MAX_PRUNED_RECEIPTS_PER_LEVEL;
+    // The retention is set by the admin, it can be too large to be added to a level
+    let is_expired = |level: u64| level.checked_add(retention).map_or(false, |l| l < current_level);
+    while budget > 0 && is_expired(level) {
+        let length = read_level_receipts_length(host, &level)?;
+        while budget > 0 && index < length {
+            let () = delete_level_receipt(host, &level, &index)?;
+            index += 1;
+            budget -= 1;
+        }
+        if index >= length {
+            // A level without receipts has no bucket
+            if length > 0 {
+                let () = delete_level_receipts(host, &level)?;
+            }
+            level += 1;
+            index = 0;
+            budget = budget.saturating_sub(1);
+        }
+    }
+
+    store_pruning_cursor(host, &level, &index)
+}
+
 /// Verify the signature of a message
 ///
 /// Returns the inner message
@@ -114,38 +339,326 @@ pub fn verify_nonce(inner: Inner, nonce: &Nonce) -> Result<Content> {
     }
 }
 
+/// Verify that a message is signed by the admin, with the next admin nonce
+///
+/// Returns the content of the message
+pub fn verify_admin(
+    message: AdminMessage,
+    admin: Option<&PublicKeyHash>,
+    nonce: &Nonce,
+) -> Result<AdminContent> {
+    let public_key_hash = PublicKeyHash::from(message.public_key());
+    if admin != Some(&public_key_hash) {
+        return Err(Error::NotAdmin);
+    }
+    let hash = message.hash();
+    let () = message.signature().verify(message.public_key(), hash.as_ref())?;
+
+    let AdminMessage { inner, .. } = message;
+    if inner.nonce() != &nonce.next() {
+        return Err(Error::InvalidNonce);
+    }
+    Ok(inner.content)
+}
+
+/// Change the layer 1 contract receiving the collected tweets
+///
+/// The deposits are then only accepted from the new contract
+pub fn set_l1_contract<S: Storage>(store: &mut S, l1_contract: SetL1Contract) -> Result<()> {
+    let SetL1Contract {
+        address,
+        entrypoint,
+    } = l1_contract;
+    match Contract::from_b58check(&address) {
+        Ok(Contract::Originated(_)) => {}
+        _ => return Err(Error::InvalidDestination),
+    }
+    let _ = Entrypoint::try_from(entrypoint.clone()).map_err(Error::from)?;
+    store_l1_contract(store, &address, &entrypoint)
+}
+
+/// Decode a root preimage hash encoded in hexadecimal
+fn read_preimage_hash(root_hash: &str) -> Result<[u8; PREIMAGE_HASH_SIZE]> {
+    let is_hex = root_hash.bytes().all(|digit| digit.is_ascii_hexdigit());
+    if root_hash.len() != 2 * PREIMAGE_HASH_SIZE || !is_hex {
+        return Err(Error::InvalidPreimageHash);
+    }
+    let mut hash = [0; PREIMAGE_HASH_SIZE];
+    for (index, byte) in hash.iter_mut().enumerate() {
+        let digits = &root_hash[2 * index..2 * index + 2];
+        *byte = u8::from_str_radix(digits, 16).map_err(|_| Error::InvalidPreimageHash)?;
+    }
+    Ok(hash)
+}
+
+/// Schedule the installation of a new kernel
+///
+/// The kernel is revealed from the beginning of the next run, over as many runs as needed
+pub fn schedule_kernel_upgrade<S: Storage>(store: &mut S, root_hash: String) -> Result<()> {
+    let _ = read_preimage_hash(&root_hash)?;
+    store_pending_upgrade(store, &root_hash)
+}
+
+/// Push the hashes of a revealed page on the stack of pages to reveal
+///
+/// They are pushed in reverse order, so that the pages are popped in the order of the kernel
+fn push_kernel_pages<Host: Runtime>(
+    host: &mut Host,
+    stack_length: &mut u64,
+    page: V0SliceHashPage,
+) -> Result<()> {
+    let mut hashes = Vec::new();
+    for hash in page.hashes().collect::<Vec<_>>().into_iter().rev() {
+        hashes.extend_from_slice(hash);
+    }
+    let offset = *stack_length as usize * PREIMAGE_HASH_SIZE;
+    for (index, chunk) in hashes.chunks(MAX_FILE_CHUNK_SIZE).enumerate() {
+        let () = host
+            .store_write(&UPGRADE_STACK, chunk, offset + index * MAX_FILE_CHUNK_SIZE)
+            .map_err(Error::from)?;
+    }
+    *stack_length += (hashes.len() / PREIMAGE_HASH_SIZE) as u64;
+    Ok(())
+}
+
+/// Pop the hash of the next page to reveal
+fn pop_kernel_page<Host: Runtime>(
+    host: &mut Host,
+    stack_length: &mut u64,
+) -> Result<[u8; PREIMAGE_HASH_SIZE]> {
+    *stack_length -= 1;
+    let offset = *stack_length as usize * PREIMAGE_HASH_SIZE;
+    let hash = host
+        .store_read(&UPGRADE_STACK, offset, PREIMAGE_HASH_SIZE)
+        .map_err(Error::from)?;
+    hash.try_into().map_err(|_| Error::InvalidKernel)
+}
+
+/// Append the content of a revealed page to the staging path
+fn write_kernel_page<Host: Runtime>(
+    host: &mut Host,
+    size: &mut u64,
+    page: V0SliceContentPage,
+) -> Result<()> {
+    let content = page.as_ref();
+    if *size as usize + content.len() > MAX_KERNEL_SIZE {
+        return Err(Error::InvalidKernel);
+    }
+    for chunk in content.chunks(MAX_FILE_CHUNK_SIZE) {
+        let () = host
+            .store_write(&KERNEL_STAGING, chunk, *size as usize)
+            .map_err(Error::from)?;
+        *size += chunk.len() as u64;
+    }
+    Ok(())
+}
+
+/// Reveal the pages of the scheduled kernel, starting from where the previous run stopped
+///
+/// Returns the number of revealed pages
+fn reveal_kernel_pages<Host: RawRollupCore + Runtime>(
+    host: &mut Host,
+    max_pages: u64,
+) -> Result<u64> {
+    let root_hash = match read_pending_upgrade(host)? {
+        None => return Ok(0),
+        Some(root_hash) => read_preimage_hash(&root_hash)?,
+    };
+    let (mut stack_length, mut pages, mut size) = match read_upgrade_progress(host)? {
+        Some(progress) => progress,
+        None => {
+            let () = Storage::delete(host, &KERNEL_STAGING)?;
+            let () = host
+                .store_write(&UPGRADE_STACK, &root_hash, 0)
+                .map_err(Error::from)?;
+            (1, 0, 0)
+        }
+    };
+
+    let mut revealed = 0;
+    let mut buffer = [0; MAX_PAGE_SIZE];
+    while stack_length > 0 && revealed < max_pages {
+        if pages >= MAX_KERNEL_PAGES {
+            return Err(Error::InvalidKernel);
+        }
+        let hash = pop_kernel_page(host, &mut stack_length)?;
+        let (page, _) = fetch_page_raw(host, &hash, &mut buffer).map_err(|_| Error::InvalidKernel)?;
+        let page = SlicePage::try_from(page).map_err(|_| Error::InvalidKernel)?;
+        let () = match page {
+            SlicePage::V0HashPage(page) => push_kernel_pages(host, &mut stack_length, page)?,
+            SlicePage::V0ContentPage(page) => write_kernel_page(host, &mut size, page)?,
+        };
+        pages += 1;
+        revealed += 1;
+    }
+
+    if stack_length > 0 {
+        let () = store_upgrade_progress(host, &stack_length, &pages, &size)?;
+        return Ok(revealed);
+    }
+
+    if size < WASM_MAGIC.len() as u64 {
+        return Err(Error::InvalidKernel);
+    }
+    let magic = host
+        .store_read(&KERNEL_STAGING, 0, WASM_MAGIC.len())
+        .map_err(Error::from)?;
+    if magic != WASM_MAGIC {
+        return Err(Error::InvalidKernel);
+    }
+    let () = host
+        .store_move(&KERNEL_STAGING, &KERNEL_BOOT)
+        .map_err(Error::from)?;
+    let () = delete_upgrade(host)?;
+    Ok(revealed)
+}
+
+/// Install the scheduled kernel
+///
+/// The pages are revealed to a staging path, each page being authenticated by its hash
+/// At most max_pages pages are revealed during a run, the installation resumes at the next run
+/// The current kernel is only replaced if the new one is entirely revealed within the size limit
+/// And starts with the wasm magic number. The new kernel is loaded at the next reboot
+///
+/// A failed upgrade is discarded, so that it is only attempted once
+///
+/// Returns the number of revealed pages, 0 if there is no scheduled upgrade
+pub fn install_kernel_upgrade<Host: RawRollupCore + Runtime>(
+    host: &mut Host,
+    max_pages: u64,
+) -> Result<u64> {
+    let revealed = reveal_kernel_pages(host, max_pages);
+    if revealed.is_err() {
+        let () = delete_upgrade(host)?;
+    }
+    revealed
+}
+
 /// Create a new tweet from the PostTweet request
 /// Save the tweet to the durable state
 /// And add a tweet entry to the user account
-pub fn create_tweet<Host: RawRollupCore + Runtime>(
-    host: &mut Host,
+///
+/// The royalties of the tweet are set once and for all, within the global cap
+///
+/// Returns the id of the created tweet
+pub fn create_tweet<S: Storage>(
+    store: &mut S,
     account: &Account,
-    post_tweet: PostTweet,
-) -> Result<()> {
-    let id = increment_tweet_counter(host)?;
+    mut post_tweet: PostTweet,
+) -> Result<u64> {
+    let royalties = post_tweet.royalties;
+    if royalties > MAX_ROYALTIES {
+        return Err(Error::RoyaltiesTooHigh);
+    }
+    let media = std::mem::take(&mut post_tweet.media);
+    if media.len() > MAX_MEDIA_PER_TWEET {
+        return Err(Error::TooManyMedia);
+    }
+    let () = media.iter().try_for_each(Media::validate)?;
+
+    let id = increment_tweet_counter(store)?;
     let tweet = Tweet::from(post_tweet);
-    let _ = store_tweet(host, &id, &tweet)?;
-    let _ = add_owned_tweet_to_account(host, &account.public_key_hash, &id)?;
-    let _ = add_written_tweet_to_account(host, &account.public_key_hash, &id)?;
-    Ok(())
+    let _ = store_tweet(store, &id, &tweet)?;
+    let () = set_royalties(store, &id, &royalties)?;
+    let () = store_media(store, &id, &media)?;
+    let () = set_owner(store, &id, &account.public_key_hash)?;
+    let _ = add_written_tweet_to_account(store, &account.public_key_hash, &id)?;
+    Ok(id)
 }
 
-pub fn like_tweet<Host: RawRollupCore + Runtime>(
+/// Append the revealed pages of a long tweet to its body
+fn append_body_page<Host: Runtime>(
+    body: &mut Vec<u8>,
+) -> impl FnMut(&mut Host, V0SliceContentPage) -> std::result::Result<(), &'static str> + '_ {
+    |_, page| {
+        let content = page.as_ref();
+        if body.len() + content.len() > MAX_LONG_TWEET_SIZE {
+            return Err(LONG_TWEET_TOO_LARGE);
+        }
+        body.extend_from_slice(content);
+        Ok(())
+    }
+}
+
+/// Reveal the body of a long tweet and check it
+///
+/// The body should be valid UTF-8 text of at most MAX_LONG_TWEET_SIZE bytes
+/// It is revealed before the operation is applied, because a transaction cannot reveal preimages
+pub fn verify_long_tweet<Host: RawRollupCore + Runtime>(
     host: &mut Host,
+    post_long_tweet: &PostLongTweet,
+) -> Result<()> {
+    let root_hash = read_preimage_hash(&post_long_tweet.body)?;
+    let mut body = Vec::new();
+    let mut buffer = [0; MAX_PAGE_SIZE * MAX_DAC_LEVELS];
+    let mut save_content = append_body_page(&mut body);
+    let revealed = reveal_loop(
+        host,
+        0,
+        &root_hash,
+        &mut buffer,
+        MAX_DAC_LEVELS,
+        &mut save_content,
+    );
+    drop(save_content);
+
+    match revealed {
+        Err(LONG_TWEET_TOO_LARGE) => Err(Error::LongTweetTooLarge),
+        Err(err) => {
+            host.write_debug(err);
+            Err(Error::InvalidLongTweet)
+        }
+        Ok(()) => match String::from_utf8(body) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Error::InvalidLongTweet),
+        },
+    }
+}
+
+/// Create a new tweet from the PostLongTweet request
+///
+/// Only the root hash of the body is stored, the content of the record is empty
+pub fn create_long_tweet<S: Storage>(
+    store: &mut S,
+    account: &Account,
+    post_long_tweet: PostLongTweet,
+) -> Result<u64> {
+    let PostLongTweet {
+        author,
+        body,
+        royalties,
+        media,
+    } = post_long_tweet;
+    let post_tweet = PostTweet {
+        author,
+        content: String::new(),
+        royalties,
+        media,
+    };
+    let id = create_tweet(store, account, post_tweet)?;
+    let () = set_body(store, &id, &body)?;
+    Ok(id)
+}
+
+/// Like a tweet
+///
+/// A tweet can only be liked once by an account, and only while it is active
+pub fn like_tweet<S: Storage>(
+    store: &mut S,
+    level: u32,
     account: &Account,
     tweet_id: &u64,
 ) -> Result<()> {
-    let already_liked = is_liked(host, &account.public_key_hash, tweet_id)?;
+    let already_liked = is_liked(store, &account.public_key_hash, tweet_id)?;
     match already_liked {
         true => Err(Error::TweetAlreadyLiked),
         false => {
-            let tweet = read_tweet(host, tweet_id)?;
-            match tweet {
-                None => Err(Error::TweetNotFound),
-                Some(tweet) => {
-                    let tweet = tweet.like();
-                    store_tweet(host, tweet_id, &tweet)?;
-                    let _ = set_like_flag(host, &account.public_key_hash, &tweet_id)?;
+            let state = read_tweet_state(store, tweet_id, level)?;
+            match state.is_active() {
+                false => Err(Error::LikeOfFrozenTweet),
+                true => {
+                    let _ = increment_likes(store, tweet_id)?;
+                    let _ = set_like_flag(store, &account.public_key_hash, &tweet_id)?;
                     Ok(())
                 }
             }
@@ -156,8 +669,10 @@ pub fn like_tweet<Host: RawRollupCore + Runtime>(
 /// Transfer a tweet from an account to another one
 ///
 /// Checks if the account parameter is owner of the tweet
-pub fn transfer_tweet<Host: RawRollupCore + Runtime>(
-    host: &mut Host,
+/// And that the tweet has not been collected
+pub fn transfer_tweet<S: Storage>(
+    store: &mut S,
+    level: u32,
     account: &Account,
     transfer: &Transfer,
 ) -> Result<()> {
@@ -165,22 +680,86 @@ pub fn transfer_tweet<Host: RawRollupCore + Runtime>(
         tweet_id,
         destination,
     } = transfer;
-    let () = is_owner(host, &account.public_key_hash, tweet_id)?;
-    let () = storage::transfer(host, &account.public_key_hash, tweet_id, destination)?;
+    let () = is_owner(store, &account.public_key_hash, tweet_id)?;
+    let state = read_tweet_state(store, tweet_id, level)?;
+    if !state.is_active() {
+        return Err(Error::TransferOfFrozenTweet);
+    }
+    if is_auctioned(store, tweet_id)? {
+        return Err(Error::TweetInAuction);
+    }
+    let () = storage::transfer(store, tweet_id, destination)?;
     Ok(())
 }
 
+/// Delete a tweet
+///
+/// Only a moderator can delete a tweet, as long as it has not been collected nor auctioned
+pub fn delete_tweet<S: Storage>(
+    store: &mut S,
+    level: u32,
+    account: &Account,
+    tweet_id: &u64,
+) -> Result<()> {
+    if !is_moderator(store, &account.public_key_hash)? {
+        return Err(Error::NotModerator);
+    }
+    let state = read_tweet_state(store, tweet_id, level)?;
+    if !state.is_active() {
+        return Err(Error::DeletionOfFrozenTweet);
+    }
+    if is_auctioned(store, tweet_id)? {
+        return Err(Error::TweetInAuction);
+    }
+    storage::delete_tweet(store, tweet_id)
+}
+
+/// Write a transaction to a layer 1 contract in the outbox
+///
+/// The transaction is the only one of its batch
+fn write_outbox_transaction<S: Storage, Expr: Michelson>(
+    store: &mut S,
+    destination: &str,
+    entrypoint: &str,
+    parameters: Expr,
+) -> Result<()> {
+    let destination =
+        Contract::from_b58check(destination).map_err(|_| Error::FromBase58CheckError)?;
+    let entrypoint = Entrypoint::try_from(entrypoint.to_string()).map_err(Error::from)?;
+
+    let transaction = OutboxMessageTransaction {
+        parameters,
+        destination,
+        entrypoint,
+    };
+
+    let batch = OutboxMessageTransactionBatch::from(vec![transaction]);
+    let message = OutboxMessage::AtomicTransactionBatch(batch);
+
+    let mut output = Vec::default();
+    let () = message.bin_write(&mut output).map_err(Error::from)?;
+    store.output(&output)
+}
+
 /// Withdraw the tweet to layer 1
-pub fn withdraw_tweet<Host: RawRollupCore + Runtime>(
-    host: &mut Host,
-    previous_hash: &str,
+///
+/// The tweet is frozen: it is collecting until the outbox message is final
+pub fn withdraw_tweet<S: Storage>(
+    store: &mut S,
+    level_info: &LevelInfo,
     account: &Account,
     tweet_id: &u64,
 ) -> Result<()> {
-    let () = is_owner(host, &account.public_key_hash, tweet_id)?;
-    let () = is_not_collected(host, tweet_id)?;
+    let () = is_owner(store, &account.public_key_hash, tweet_id)?;
+    let state = read_tweet_state(store, tweet_id, level_info.level)?;
+    if !state.is_active() {
+        return Err(Error::TweetAlreadyCollected);
+    }
+    if is_auctioned(store, tweet_id)? {
+        return Err(Error::TweetInAuction);
+    }
 
-    let tweet = read_tweet(host, tweet_id)
+    let tweet = read_tweet(store, tweet_id)
         .map_err(Error::from)?
         .ok_or(Error::TweetNotFound)?;
 
@@ -201,42 +780,415 @@ pub fn withdraw_tweet<Host: RawRollupCore + Runtime>(
     };
     // What to do with that?
     let likes = {
-        let likes = tweet.likes.to_bigint().ok_or(Error::BigIntError)?;
+        let likes = read_likes(store, tweet_id)?;
+        let likes = likes.to_bigint().ok_or(Error::BigIntError)?;
         let likes = Zarith(likes);
         MichelsonInt(likes)
     };
+    let royalties = {
+        let royalties = read_royalties(store, tweet_id)?;
+        let royalties = royalties.to_bigint().ok_or(Error::BigIntError)?;
+        let royalties = Zarith(royalties);
+        MichelsonInt(royalties)
+    };
     let content = MichelsonString(tweet.content);
-
-    let destination = Contract::from_b58check(L1_TOKEN_CONTRACT_ADDRESS)
-        .map_err(|_| Error::FromBase58CheckError)?;
+    let media = {
+        let media = read_media(store, tweet_id)?
+            .into_iter()
+            .map(|Media { mime_type, size, hash }| {
+                let size = size.to_bigint().ok_or(Error::BigIntError)?;
+                let size = MichelsonInt(Zarith(size));
+                let attachment = MichelsonPair(
+                    MichelsonPair(MichelsonString(hash), MichelsonString(mime_type)),
+                    size,
+                );
+                Ok(attachment)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        MichelsonList(media)
+    };
 
     // (pair %mint
     //     (pair (nat %id) (address %owner))
-    //     (pair %token (pair (address %author) (string %content)) (nat %likes)))
+    //     (pair %token
+    //         (pair (pair (address %author) (string %content)) (nat %likes))
+    //         (pair
+    //             (list %media (pair (pair (string %hash) (string %mime_type)) (nat %size)))
+    //             (nat %royalties))))
 
     let michelson = MichelsonPair(
         MichelsonPair(id, owner),
-        MichelsonPair(MichelsonPair(author, content), likes),
+        MichelsonPair(
+            MichelsonPair(MichelsonPair(author, content), likes),
+            MichelsonPair(media, royalties),
+        ),
     );
 
-    let transaction = OutboxMessageTransaction {
-        parameters: michelson,
+    let l1_contract = read_l1_contract(store)?.ok_or(Error::L1ContractNotConfigured)?;
+    let l1_entrypoint = read_l1_entrypoint(store)?;
+    let () = write_outbox_transaction(store, &l1_contract, &l1_entrypoint, michelson)?;
+
+    // Freeze the tweets, a collected tweet cannot be sold
+    let () = delete_listing(store, tweet_id)?;
+    let () = set_tweet_state(store, tweet_id, &TweetState::Collecting(level_info.level))?;
+    let () = set_collected_block(store, tweet_id, &level_info.previous_hash)?;
+    // Indicates that the user is collecting the tweet
+    let () = add_collecting_tweet_to_account(store, &account.public_key_hash, tweet_id)?;
+    Ok(())
+}
+
+/// Give back a tweet deposited by the layer 1 token contract
+///
+/// The token has been burnt on layer 1, so the tweet is unfrozen
+/// And its ownership is given to the receiver named in the deposit
+///
+/// The outbox message minting the token must be final, so the tweet has to be collected
+pub fn deposit_tweet<S: Storage>(store: &mut S, level: u32, deposit: &TweetDeposit) -> Result<()> {
+    let TweetDeposit { tweet_id, receiver } = deposit;
+    let state = read_tweet_state(store, tweet_id, level)?;
+    if state != TweetState::Collected {
+        return Err(Error::TweetNotCollected);
+    }
+
+    let owner = read_owner(store, tweet_id)?.ok_or(Error::TweetNotFound)?;
+    let () = remove_collecting_tweet_from_account(store, &owner, tweet_id)?;
+    let () = delete_collected_block(store, tweet_id)?;
+    let () = set_tweet_state(store, tweet_id, &TweetState::Active)?;
+    storage::transfer(store, tweet_id, receiver)
+}
+
+/// Credit the tickets deposited by a ticketer to the balance of the receiver
+pub fn deposit_ticket<S: Storage>(store: &mut S, deposit: &TicketDeposit) -> Result<()> {
+    let TicketDeposit {
+        ticket,
+        ticketer,
+        amount,
+        receiver,
+    } = deposit;
+    let () = store_ticketer(store, ticket, ticketer)?;
+    let _ = credit_balance(store, receiver, ticket, amount)?;
+    Ok(())
+}
+
+/// Withdraw tickets to a layer 1 contract
+///
+/// The amount is debited from the balance of the account
+/// And the tickets are sent to the destination in an outbox message
+pub fn withdraw_ticket<S: Storage>(
+    store: &mut S,
+    account: &Account,
+    withdraw: Withdraw,
+) -> Result<()> {
+    let Withdraw {
+        ticket,
+        amount,
         destination,
-        entrypoint: Entrypoint::try_from(L1_TOKEN_CONTRACT_ENTRYPOINT.to_string())
-            .map_err(Error::from)?,
-    };
+        entrypoint,
+    } = withdraw;
+    let ticket = TicketHash::try_from(ticket).map_err(|_| Error::TicketNotFound)?;
+    let ticketer = read_ticketer(store, &ticket)?.ok_or(Error::TicketNotFound)?;
 
-    let batch = OutboxMessageTransactionBatch::from(vec![transaction]);
-    let message = OutboxMessage::AtomicTransactionBatch(batch);
+    // Only smart contracts can receive the tickets of an outbox message
+    match Contract::from_b58check(&destination) {
+        Ok(Contract::Originated(_)) => {}
+        _ => return Err(Error::InvalidDestination),
+    }
 
-    let mut output = Vec::default();
-    let () = message.bin_write(&mut output).unwrap();
+    let ticketer =
+        Contract::from_b58check(&ticketer).map_err(|_| Error::FromBase58CheckError)?;
+    let michelson =
+        UnitTicket::new(ticketer, MichelsonUnit, amount).map_err(|_| Error::InvalidAmount)?;
 
-    let () = host.write_output(&output).unwrap();
+    let _ = debit_balance(store, &account.public_key_hash, &ticket, &amount)?;
+    write_outbox_transaction(store, &destination, &entrypoint, michelson)
+}
 
-    // Freeze the tweets
-    let () = set_collected_block(host, tweet_id, previous_hash)?;
-    // Indicates that the user is collecting the tweet
-    let () = add_collecting_tweet_to_account(host, &account.public_key_hash, tweet_id)?;
+/// Tip the author of a tweet
+///
+/// The tickets are moved from the balance of the account to the balance of the author,
+/// Even if the tweet is now owned by someone else
+pub fn tip_tweet<S: Storage>(store: &mut S, account: &Account, tip: Tip) -> Result<()> {
+    let Tip {
+        tweet_id,
+        ticket,
+        amount,
+    } = tip;
+    if amount == 0 {
+        return Err(Error::InvalidAmount);
+    }
+    let ticket = TicketHash::try_from(ticket).map_err(|_| Error::TicketNotFound)?;
+    let tweet = read_tweet(store, &tweet_id)?.ok_or(Error::TweetNotFound)?;
+
+    let _ = debit_balance(store, &account.public_key_hash, &ticket, &amount)?;
+    let _ = credit_balance(store, &tweet.author, &ticket, &amount)?;
+    add_tip(store, &tweet_id, &tweet.author, &ticket, &amount)
+}
+
+/// Put a tweet on sale
+///
+/// Only the owner can list a tweet, as long as it has not been collected
+pub fn list_tweet<S: Storage>(
+    store: &mut S,
+    level: u32,
+    account: &Account,
+    sale: Sale,
+) -> Result<()> {
+    let Sale {
+        tweet_id,
+        ticket,
+        price,
+    } = sale;
+    let () = is_owner(store, &account.public_key_hash, &tweet_id)?;
+    let state = read_tweet_state(store, &tweet_id, level)?;
+    if !state.is_active() {
+        return Err(Error::ListingOfFrozenTweet);
+    }
+    if is_auctioned(store, &tweet_id)? {
+        return Err(Error::TweetInAuction);
+    }
+
+    let ticket = TicketHash::try_from(ticket).map_err(|_| Error::TicketNotFound)?;
+    let _ = read_ticketer(store, &ticket)?.ok_or(Error::TicketNotFound)?;
+    store_listing(store, &tweet_id, &Listing { ticket, price })
+}
+
+/// Remove a tweet from the market
+pub fn cancel_listing<S: Storage>(store: &mut S, account: &Account, tweet_id: &u64) -> Result<()> {
+    let () = is_owner(store, &account.public_key_hash, tweet_id)?;
+    let _ = read_listing(store, tweet_id)?.ok_or(Error::NotListed)?;
+    delete_listing(store, tweet_id)
+}
+
+/// Pay the price of a sale to the seller
+///
+/// The royalties of the tweet are paid to its author, the seller receives the rest
+fn pay_sale<S: Storage>(
+    store: &mut S,
+    tweet_id: &u64,
+    seller: &PublicKeyHash,
+    ticket: &TicketHash,
+    price: &u64,
+) -> Result<()> {
+    let tweet = read_tweet(store, tweet_id)?.ok_or(Error::TweetNotFound)?;
+    let royalties = read_royalties(store, tweet_id)?;
+    // The royalties are at most 100%, so the amount always fits in a u64
+    let royalty = u128::from(*price) * u128::from(royalties) / 100;
+    let royalty = u64::try_from(royalty).map_err(|_| Error::BalanceOverflow)?;
+    let proceeds = price.checked_sub(royalty).ok_or(Error::InsufficientBalance)?;
+
+    let _ = credit_balance(store, &tweet.author, ticket, &royalty)?;
+    let _ = credit_balance(store, seller, ticket, &proceeds)?;
     Ok(())
 }
+
+/// Buy a tweet on sale
+///
+/// The price is paid to the owner and the tweet is transferred to the buyer in the same step
+/// The listing must match the price signed by the buyer
+pub fn buy_tweet<S: Storage>(
+    store: &mut S,
+    level: u32,
+    account: &Account,
+    sale: Sale,
+) -> Result<()> {
+    let Sale {
+        tweet_id,
+        ticket,
+        price,
+    } = sale;
+    let listing = read_listing(store, &tweet_id)?.ok_or(Error::NotListed)?;
+    if listing.ticket.to_string() != ticket || listing.price != price {
+        return Err(Error::PriceMismatch);
+    }
+    let state = read_tweet_state(store, &tweet_id, level)?;
+    if !state.is_active() {
+        return Err(Error::TransferOfFrozenTweet);
+    }
+
+    let owner = read_owner(store, &tweet_id)?.ok_or(Error::TweetNotFound)?;
+    let _ = debit_balance(store, &account.public_key_hash, &listing.ticket, &price)?;
+    let () = pay_sale(store, &tweet_id, &owner, &listing.ticket, &price)?;
+    // The transfer removes the listing
+    storage::transfer(store, &tweet_id, &account.public_key_hash)
+}
+
+/// Auction a tweet
+///
+/// Only the owner can auction a tweet, as long as it has not been collected
+/// The tweet is removed from the market, and cannot be transferred until the auction is settled
+pub fn open_auction<S: Storage>(
+    store: &mut S,
+    level: u32,
+    account: &Account,
+    open_auction: OpenAuction,
+) -> Result<()> {
+    let OpenAuction {
+        tweet_id,
+        ticket,
+        reserve,
+        end_level,
+    } = open_auction;
+    let () = is_owner(store, &account.public_key_hash, &tweet_id)?;
+    let state = read_tweet_state(store, &tweet_id, level)?;
+    if !state.is_active() {
+        return Err(Error::ListingOfFrozenTweet);
+    }
+    if is_auctioned(store, &tweet_id)? {
+        return Err(Error::TweetInAuction);
+    }
+    if end_level < level {
+        return Err(Error::InvalidEndLevel);
+    }
+    if end_level - level > MAX_AUCTION_DURATION {
+        return Err(Error::AuctionTooLong);
+    }
+
+    let ticket = TicketHash::try_from(ticket).map_err(|_| Error::TicketNotFound)?;
+    let _ = read_ticketer(store, &ticket)?.ok_or(Error::TicketNotFound)?;
+
+    let () = delete_listing(store, &tweet_id)?;
+    let () = add_auction_to_level(store, &end_level.into(), &tweet_id)?;
+    let auction = Auction {
+        seller: account.public_key_hash.clone(),
+        ticket,
+        reserve,
+        end_level,
+        best_bid: None,
+    };
+    store_auction(store, &tweet_id, &auction)
+}
+
+/// Bid on an auctioned tweet
+///
+/// The amount of the bid is escrowed, and the previous best bid is refunded
+pub fn place_bid<S: Storage>(store: &mut S, level: u32, account: &Account, bid: Bid) -> Result<()> {
+    let Bid { tweet_id, amount } = bid;
+    let auction = read_auction(store, &tweet_id)?.ok_or(Error::AuctionNotFound)?;
+    if level > auction.end_level {
+        return Err(Error::AuctionEnded);
+    }
+
+    let minimum = match &auction.best_bid {
+        None => auction.reserve,
+        Some(best_bid) => best_bid.amount.checked_add(1).ok_or(Error::BalanceOverflow)?,
+    };
+    if amount < minimum {
+        return Err(Error::BidTooLow);
+    }
+
+    if let Some(BestBid { bidder, amount }) = &auction.best_bid {
+        let _ = credit_balance(store, bidder, &auction.ticket, amount)?;
+    }
+    let _ = debit_balance(store, &account.public_key_hash, &auction.ticket, &amount)?;
+
+    let best_bid = Some(BestBid {
+        bidder: account.public_key_hash.clone(),
+        amount,
+    });
+    store_auction(store, &tweet_id, &Auction { best_bid, ..auction })
+}
+
+/// Settle an ended auction
+///
+/// The best bid is paid to the seller and the tweet is given to the bidder
+/// If there is no bid, the seller keeps the tweet
+fn settle_auction<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<()> {
+    let auction = match read_auction(store, tweet_id)? {
+        None => return Ok(()),
+        Some(auction) => auction,
+    };
+    let () = delete_auction(store, tweet_id)?;
+    match auction.best_bid {
+        None => Ok(()),
+        Some(BestBid { bidder, amount }) => {
+            let () = pay_sale(store, tweet_id, &auction.seller, &auction.ticket, &amount)?;
+            storage::transfer(store, tweet_id, &bidder)
+        }
+    }
+}
+
+/// Cancel an ended auction whose settlement has failed
+///
+/// The best bid is refunded to the bidder and the seller keeps the tweet
+fn cancel_auction<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<()> {
+    let auction = match read_auction(store, tweet_id)? {
+        None => return Ok(()),
+        Some(auction) => auction,
+    };
+    let () = delete_auction(store, tweet_id)?;
+    match auction.best_bid {
+        None => Ok(()),
+        Some(BestBid { bidder, amount }) => {
+            let _ = credit_balance(store, &bidder, &auction.ticket, &amount)?;
+            Ok(())
+        }
+    }
+}
+
+/// Settle the auctions that have ended before the given level
+///
+/// The buckets are settled in the order of their levels, from the settlement cursor
+/// At most MAX_AUCTIONS_PER_LEVEL auctions or levels are visited per call,
+/// The remaining auctions are settled during the next levels
+///
+/// Each auction is settled in its own transaction, so that a failing settlement does not
+/// Prevent the other ones. A failed settlement is rolled back and the auction is cancelled,
+/// So that the tweet and the escrowed bid are not locked once the bucket is deleted
+///
+/// Returns the number of visited auctions and levels
+pub fn settle_auctions<Host: RawRollupCore + Runtime>(host: &mut Host, level: u32) -> Result<u64> {
+    let current_level = u64::from(level);
+
+    // There are no auctions ending before the first settlement
+    let (mut ended_level, mut index) =
+        read_settlement_cursor(host)?.unwrap_or((current_level.saturating_sub(1), 0));
+
+    let mut budget = MAX_AUCTIONS_PER_LEVEL;
+    while budget > 0 && ended_level < current_level {
+        let length = read_level_auctions_length(host, &ended_level)?;
+        while budget > 0 && index < length {
+            let tweet_id = read_level_auction(host, &ended_level, &index)?;
+            let () = settle_or_cancel_auction(host, &tweet_id)?;
+            index += 1;
+            budget -= 1;
+        }
+        if index >= length {
+            // A level without auctions has no bucket
+            if length > 0 {
+                let () = delete_level_auctions(host, &ended_level)?;
+            }
+            ended_level += 1;
+            index = 0;
+            budget = budget.saturating_sub(1);
+        }
+    }
+
+    let () = store_settlement_cursor(host, &ended_level, &index)?;
+    Ok(MAX_AUCTIONS_PER_LEVEL - budget)
+}
+
+/// Settle an ended auction, or cancel it if the settlement fails
+///
+/// Only the runtime errors are returned, the other ones are logged
+fn settle_or_cancel_auction<Host: RawRollupCore + Runtime>(
+    host: &mut Host,
+    tweet_id: &u64,
+) -> Result<()> {
+    let result = Transaction::run(host, |transaction| settle_auction(transaction, tweet_id));
+    let result = match result {
+        Err(Error::Runtime(err)) => return Err(Error::Runtime(err)),
+        Err(err) => {
+            host.write_debug(&err.to_string());
+            Transaction::run(host, |transaction| cancel_auction(transaction, tweet_id))
+        }
+        Ok(()) => Ok(()),
+    };
+    match result {
+        Err(Error::Runtime(err)) => Err(Error::Runtime(err)),
+        Err(err) => {
+            host.write_debug(&err.to_string());
+            Ok(())
+        }
+        Ok(()) => Ok(()),
+    }
+}
@@ -0,0 +1,564 @@
+use std::marker::PhantomData;
+
+use host::path::{OwnedPath, Path, RefPath};
+
+use crate::core::error::*;
+use crate::core::media::Media;
+use crate::core::nonce::Nonce;
+use crate::core::public_key_hash::PublicKeyHash;
+use crate::core::tweet::{Tweet, TweetState};
+use crate::storage::{Flag, Storage, Value};
+use crypto::hash::ContractKt1Hash;
+use tezos_rollup_encoding::michelson::ticket::TicketHash;
+
+/// Typed location of a value in the durable state
+///
+/// The type of the value is known from its key,
+/// So the value is always encoded and decoded the same way
+pub struct Key<V, P = OwnedPath> {
+    path: P,
+    value: PhantomData<V>,
+}
+
+impl<V, P> Key<V, P> {
+    pub const fn new(path: P) -> Self {
+        Key {
+            path,
+            value: PhantomData,
+        }
+    }
+}
+
+impl<V: Value, P: Path> Key<V, P> {
+    /// Read the value, returns None if it is not present
+    pub fn read<S: Storage>(&self, store: &mut S) -> Result<Option<V>> {
+        store.read(&self.path)?.map(V::decode).transpose()
+    }
+
+    /// Write the value, the previous one is overwritten
+    pub fn write<S: Storage>(&self, store: &mut S, value: &V) -> Result<()> {
+        store.write(&self.path, &value.encode())
+    }
+
+    /// Check if the value is present
+    pub fn exists<S: Storage>(&self, store: &mut S) -> Result<bool> {
+        store.has(&self.path)
+    }
+
+    /// Delete the value
+    pub fn delete<S: Storage>(&self, store: &mut S) -> Result<()> {
+        store.delete(&self.path)
+    }
+}
+
+/// Builds a path from its segments
+///
+/// Each segment is prefixed by a slash
+fn path(segments: &[&str]) -> Result<OwnedPath> {
+    let path: String = segments
+        .iter()
+        .map(|segment| format!("/{}", segment))
+        .collect();
+    OwnedPath::try_from(path).map_err(Error::from)
+}
+
+/// Builds a key from the segments of its path
+fn key<V>(segments: &[&str]) -> Result<Key<V>> {
+    path(segments).map(Key::new)
+}
+
+/// Number of created tweets, it is also the id of the next tweet
+pub const TWEET_COUNTER: Key<u64, RefPath> =
+    Key::new(RefPath::assert_from(b"/constants/tweet-counter")); // The name constants is not appropriate
+
+/// Number of levels a receipt is kept
+pub const RECEIPT_RETENTION: Key<u64, RefPath> =
+    Key::new(RefPath::assert_from(b"/config/receipt_retention"));
+
+/// Layer 1 contract receiving the collected tweets, and sending them back
+/// It is set at install time
+pub const L1_CONTRACT: Key<String, RefPath> =
+    Key::new(RefPath::assert_from(b"/config/l1_contract"));
+
+/// Entrypoint of the layer 1 contract called to mint the collected tweets
+pub const L1_ENTRYPOINT: Key<String, RefPath> =
+    Key::new(RefPath::assert_from(b"/config/l1_entrypoint"));
+
+/// Account allowed to sign the admin messages
+/// It is set at install time
+pub const ADMIN: Key<PublicKeyHash, RefPath> = Key::new(RefPath::assert_from(b"/config/admin"));
+
+/// Nonce of the last admin message
+pub const ADMIN_NONCE: Key<u64, RefPath> = Key::new(RefPath::assert_from(b"/admin/nonce"));
+
+/// Layer 1 contract allowed to send admin operations
+pub const GOVERNANCE: Key<ContractKt1Hash, RefPath> =
+    Key::new(RefPath::assert_from(b"/config/governance"));
+
+/// Address of this rollup, the transfers sent to another rollup are ignored
+/// It is revealed from the metadata of the rollup the first time it is needed
+pub const ROLLUP_ADDRESS: Key<String, RefPath> =
+    Key::new(RefPath::assert_from(b"/config/rollup_address"));
+
+/// Present if the layer 1 contract is allowed to deposit its tickets on the rollup
+/// /config/ticketers/{KT1...}
+pub fn whitelisted_ticketer(ticketer: &str) -> Result<Key<Flag>> {
+    key(&["config", "ticketers", ticketer])
+}
+
+/// Present while the processing of the user operations is paused
+pub const PAUSED: Key<Flag, RefPath> = Key::new(RefPath::assert_from(b"/config/paused"));
+
+/// Hash of the receipt of an admin message
+/// /admin/receipts/{nonce}
+pub fn admin_receipt(nonce: &Nonce) -> Result<Key<String>> {
+    key(&["admin", "receipts", &nonce.0.to_string()])
+}
+
+/// Present if the account is a moderator
+/// /moderators/{tz1...}
+pub fn moderator(public_key_hash: &PublicKeyHash) -> Result<Key<Flag>> {
+    key(&["moderators", &public_key_hash.to_string()])
+}
+
+/// Level of the next receipt to prune
+pub const PRUNING_LEVEL: Key<u64, RefPath> = Key::new(RefPath::assert_from(b"/gc/receipts/level"));
+
+/// Index in its level of the next receipt to prune
+pub const PRUNING_INDEX: Key<u64, RefPath> = Key::new(RefPath::assert_from(b"/gc/receipts/index"));
+
+/// Level of the next auctions bucket to settle
+pub const SETTLEMENT_LEVEL: Key<u64, RefPath> =
+    Key::new(RefPath::assert_from(b"/gc/auctions/level"));
+
+/// Index in its level of the next auction to settle
+pub const SETTLEMENT_INDEX: Key<u64, RefPath> =
+    Key::new(RefPath::assert_from(b"/gc/auctions/index"));
+
+/// Level of the inbox being processed
+pub const INBOX_LEVEL: Key<u64, RefPath> = Key::new(RefPath::assert_from(b"/inbox/level"));
+
+/// Hash of the block preceding the inbox being processed
+pub const INBOX_PREVIOUS_HASH: Key<String, RefPath> =
+    Key::new(RefPath::assert_from(b"/inbox/previous_hash"));
+
+/// Present when the kernel has been rebooted in the middle of an inbox
+pub const INBOX_RESUME: Key<Flag, RefPath> = Key::new(RefPath::assert_from(b"/inbox/resume"));
+
+/// State of the kernel upgrade, deleted once the upgrade is installed or discarded
+pub const UPGRADE: RefPath = RefPath::assert_from(b"/upgrade");
+
+/// Root preimage hash of the kernel being installed
+pub const PENDING_UPGRADE: Key<String, RefPath> =
+    Key::new(RefPath::assert_from(b"/upgrade/root_hash"));
+
+/// Hashes of the pages of the kernel left to reveal, the next one being the last
+pub const UPGRADE_STACK: RefPath = RefPath::assert_from(b"/upgrade/stack");
+
+/// Number of hashes in the stack of pages left to reveal
+pub const UPGRADE_STACK_LENGTH: Key<u64, RefPath> =
+    Key::new(RefPath::assert_from(b"/upgrade/stack_length"));
+
+/// Number of pages of the kernel already revealed
+pub const UPGRADE_PAGES: Key<u64, RefPath> = Key::new(RefPath::assert_from(b"/upgrade/pages"));
+
+/// Number of bytes of the kernel already written to the staging path
+pub const UPGRADE_SIZE: Key<u64, RefPath> = Key::new(RefPath::assert_from(b"/upgrade/size"));
+
+/// Where the new kernel is written while its pages are revealed
+pub const KERNEL_STAGING: RefPath = RefPath::assert_from(b"/upgrade/kernel.wasm");
+
+/// The kernel loaded by the host at each reboot
+pub const KERNEL_BOOT: RefPath = RefPath::assert_from(b"/kernel/boot.wasm");
+
+/// Asks the kernel to be rebooted at the end of the run
+pub const REBOOT: Key<Flag, RefPath> = Key::new(RefPath::assert_from(b"/kernel/env/reboot"));
+
+/// Keys of an account
+/// /accounts/{tz1...}
+pub struct AccountKeys {
+    public_key_hash: String,
+}
+
+pub fn account(public_key_hash: &PublicKeyHash) -> AccountKeys {
+    AccountKeys {
+        public_key_hash: public_key_hash.to_string(),
+    }
+}
+
+impl AccountKeys {
+    fn field<V>(&self, field: &[&str]) -> Result<Key<V>> {
+        let segments = [&["accounts", self.public_key_hash.as_str()][..], field].concat();
+        key(&segments)
+    }
+
+    /// /accounts/{tz1...}/nonce
+    pub fn nonce(&self) -> Result<Key<u64>> {
+        self.field(&["nonce"])
+    }
+
+    /// Present if the account has liked the tweet
+    /// /accounts/{tz1...}/likes/{id}
+    pub fn like(&self, tweet_id: &u64) -> Result<Key<Flag>> {
+        self.field(&["likes", &tweet_id.to_string()])
+    }
+
+    /// Present if the account is collecting the tweet
+    /// /accounts/{tz1...}/collecting/{id}
+    pub fn collecting(&self, tweet_id: &u64) -> Result<Key<Flag>> {
+        self.field(&["collecting", &tweet_id.to_string()])
+    }
+
+    /// Present if the account owns the tweet
+    /// /accounts/{tz1...}/tweets/owned/{id}
+    ///
+    /// It is an index of the owner field of the tweets, kept in sync by set_owner
+    pub fn owned_tweet(&self, tweet_id: &u64) -> Result<Key<Flag>> {
+        self.field(&["tweets", "owned", &tweet_id.to_string()])
+    }
+
+    /// Present if the account has written the tweet
+    /// /accounts/{tz1...}/tweets/written/{id}
+    pub fn written_tweet(&self, tweet_id: &u64) -> Result<Key<Flag>> {
+        self.field(&["tweets", "written", &tweet_id.to_string()])
+    }
+
+    /// Hash of the receipt of the operation signed with the given nonce
+    /// /accounts/{tz1...}/receipts/{nonce}
+    pub fn receipt(&self, nonce: &Nonce) -> Result<Key<String>> {
+        self.field(&["receipts", &nonce.0.to_string()])
+    }
+
+    /// Amount of tickets owned by the account
+    /// /accounts/{tz1...}/balances/{ticket_hash}
+    pub fn balance(&self, ticket: &TicketHash) -> Result<Key<u64>> {
+        self.field(&["balances", &ticket.to_string()])
+    }
+
+    /// Total amount of tickets received as tips by the author
+    /// /accounts/{tz1...}/tips/{ticket_hash}
+    pub fn tips(&self, ticket: &TicketHash) -> Result<Key<u64>> {
+        self.field(&["tips", &ticket.to_string()])
+    }
+}
+
+/// Keys of a tweet
+/// /tweets/{id}
+pub struct TweetKeys {
+    tweet_id: String,
+}
+
+pub fn tweet(tweet_id: &u64) -> TweetKeys {
+    TweetKeys {
+        tweet_id: tweet_id.to_string(),
+    }
+}
+
+impl TweetKeys {
+    fn field<V>(&self, field: &[&str]) -> Result<Key<V>> {
+        let segments = [&["tweets", self.tweet_id.as_str()][..], field].concat();
+        key(&segments)
+    }
+
+    /// Author and content of the tweet, encoded as one record
+    /// /tweets/{id}/record
+    pub fn record(&self) -> Result<Key<Tweet>> {
+        self.field(&["record"])
+    }
+
+    /// The owner of the tweet, this is the source of truth for the ownership
+    /// /tweets/{id}/owner
+    pub fn owner(&self) -> Result<Key<PublicKeyHash>> {
+        self.field(&["owner"])
+    }
+
+    /// Number of likes, stored apart from the record so that a like only writes 8 bytes
+    /// /tweets/{id}/likes
+    pub fn likes(&self) -> Result<Key<u64>> {
+        self.field(&["likes"])
+    }
+
+    /// Root preimage hash of the body of a long tweet, encoded in hexadecimal
+    /// /tweets/{id}/body
+    pub fn body(&self) -> Result<Key<String>> {
+        self.field(&["body"])
+    }
+
+    /// Attachment of the tweet, the media are indexed from 0
+    /// /tweets/{id}/media/{n}
+    pub fn media(&self, index: &usize) -> Result<Key<Media>> {
+        self.field(&["media", &index.to_string()])
+    }
+
+    /// Percentage of the sales paid to the author, set when the tweet is posted
+    /// /tweets/{id}/royalties
+    pub fn royalties(&self) -> Result<Key<u64>> {
+        self.field(&["royalties"])
+    }
+
+    /// Life cycle of the tweet
+    /// /tweets/{id}/state
+    pub fn state(&self) -> Result<Key<TweetState>> {
+        self.field(&["state"])
+    }
+
+    /// Path of the whole tweet
+    pub fn root(&self) -> Result<OwnedPath> {
+        path(&["tweets", &self.tweet_id])
+    }
+
+    /// Total amount of tickets received as tips by the tweet
+    /// /tweets/{id}/tips/{ticket_hash}
+    pub fn tips(&self, ticket: &TicketHash) -> Result<Key<u64>> {
+        self.field(&["tips", &ticket.to_string()])
+    }
+
+    /// Hash of the block preceding the level the tweet has been collected
+    /// /tweets/{id}/collected_hash
+    pub fn collected_hash(&self) -> Result<Key<String>> {
+        self.field(&["collected_hash"])
+    }
+}
+
+/// Keys of a ticket deposited on the rollup
+/// /tickets/{ticket_hash}
+pub struct TicketKeys {
+    ticket_hash: String,
+}
+
+pub fn ticket(ticket: &TicketHash) -> TicketKeys {
+    TicketKeys {
+        ticket_hash: ticket.to_string(),
+    }
+}
+
+impl TicketKeys {
+    /// Address of the layer 1 contract that has created the ticket
+    /// /tickets/{ticket_hash}/ticketer
+    pub fn ticketer(&self) -> Result<Key<String>> {
+        key(&["tickets", &self.ticket_hash, "ticketer"])
+    }
+}
+
+/// Keys of the listing of a tweet
+/// /market/{id}
+pub struct ListingKeys {
+    tweet_id: String,
+}
+
+pub fn listing(tweet_id: &u64) -> ListingKeys {
+    ListingKeys {
+        tweet_id: tweet_id.to_string(),
+    }
+}
+
+impl ListingKeys {
+    /// Path of the whole listing
+    pub fn root(&self) -> Result<OwnedPath> {
+        path(&["market", &self.tweet_id])
+    }
+
+    /// Hash of the ticket the price is paid with
+    /// /market/{id}/ticket
+    pub fn ticket(&self) -> Result<Key<String>> {
+        key(&["market", &self.tweet_id, "ticket"])
+    }
+
+    /// /market/{id}/price
+    pub fn price(&self) -> Result<Key<u64>> {
+        key(&["market", &self.tweet_id, "price"])
+    }
+}
+
+/// Keys of the auction of a tweet
+/// /auctions/{id}
+pub struct AuctionKeys {
+    tweet_id: String,
+}
+
+pub fn auction(tweet_id: &u64) -> AuctionKeys {
+    AuctionKeys {
+        tweet_id: tweet_id.to_string(),
+    }
+}
+
+impl AuctionKeys {
+    fn field<V>(&self, field: &str) -> Result<Key<V>> {
+        key(&["auctions", &self.tweet_id, field])
+    }
+
+    /// Path of the whole auction
+    pub fn root(&self) -> Result<OwnedPath> {
+        path(&["auctions", &self.tweet_id])
+    }
+
+    /// /auctions/{id}/seller
+    pub fn seller(&self) -> Result<Key<PublicKeyHash>> {
+        self.field("seller")
+    }
+
+    /// Hash of the ticket the bids are paid with
+    /// /auctions/{id}/ticket
+    pub fn ticket(&self) -> Result<Key<String>> {
+        self.field("ticket")
+    }
+
+    /// /auctions/{id}/reserve
+    pub fn reserve(&self) -> Result<Key<u64>> {
+        self.field("reserve")
+    }
+
+    /// Last level bids are accepted
+    /// /auctions/{id}/end_level
+    pub fn end_level(&self) -> Result<Key<u64>> {
+        self.field("end_level")
+    }
+
+    /// Only present once a bid has been placed
+    /// /auctions/{id}/bidder
+    pub fn bidder(&self) -> Result<Key<PublicKeyHash>> {
+        self.field("bidder")
+    }
+
+    /// Amount escrowed by the bidder
+    /// /auctions/{id}/bid
+    pub fn bid(&self) -> Result<Key<u64>> {
+        self.field("bid")
+    }
+}
+
+/// Keys of the auctions bucket of a level
+/// /auctions_by_level/{level}
+///
+/// The bucket contains the ids of the tweets whose auction ends at this level under /{index}
+/// And the number of auctions under /length
+pub struct LevelAuctionsKeys {
+    level: String,
+}
+
+pub fn level_auctions(level: &u64) -> LevelAuctionsKeys {
+    LevelAuctionsKeys {
+        level: level.to_string(),
+    }
+}
+
+impl LevelAuctionsKeys {
+    /// Path of the whole bucket
+    pub fn root(&self) -> Result<OwnedPath> {
+        path(&["auctions_by_level", &self.level])
+    }
+
+    /// /auctions_by_level/{level}/length
+    pub fn length(&self) -> Result<Key<u64>> {
+        key(&["auctions_by_level", &self.level, "length"])
+    }
+
+    /// Id of the nth tweet whose auction ends at the level
+    /// /auctions_by_level/{level}/{index}
+    pub fn auction(&self, index: &u64) -> Result<Key<u64>> {
+        key(&["auctions_by_level", &self.level, &index.to_string()])
+    }
+}
+
+/// Keys of a receipt
+/// /receipts/{hash}
+pub struct ReceiptKeys {
+    receipt_hash: String,
+}
+
+pub fn receipt(receipt_hash: &str) -> ReceiptKeys {
+    ReceiptKeys {
+        receipt_hash: receipt_hash.to_string(),
+    }
+}
+
+impl ReceiptKeys {
+    fn field<V>(&self, field: &str) -> Result<Key<V>> {
+        key(&["receipts", &self.receipt_hash, field])
+    }
+
+    /// Path of the whole receipt
+    pub fn root(&self) -> Result<OwnedPath> {
+        path(&["receipts", &self.receipt_hash])
+    }
+
+    /// /receipts/{hash}/success
+    pub fn success(&self) -> Result<Key<bool>> {
+        self.field("success")
+    }
+
+    /// Level of the inbox the message has been read from
+    /// /receipts/{hash}/level
+    pub fn level(&self) -> Result<Key<u64>> {
+        self.field("level")
+    }
+
+    /// Index of the message in its inbox level
+    /// /receipts/{hash}/index
+    pub fn index(&self) -> Result<Key<u64>> {
+        self.field("index")
+    }
+
+    /// Only present when the message has been decoded
+    /// /receipts/{hash}/operation
+    pub fn operation(&self) -> Result<Key<u64>> {
+        self.field("operation")
+    }
+
+    /// Only present when the operation has failed
+    /// /receipts/{hash}/error
+    pub fn error(&self) -> Result<Key<u64>> {
+        self.field("error")
+    }
+
+    /// Only present when a tweet has been created
+    /// /receipts/{hash}/tweet_id
+    pub fn tweet_id(&self) -> Result<Key<u64>> {
+        self.field("tweet_id")
+    }
+
+    /// Account the receipt is indexed in, absent for the admin messages
+    /// /receipts/{hash}/signer
+    pub fn signer(&self) -> Result<Key<PublicKeyHash>> {
+        self.field("signer")
+    }
+
+    /// Nonce the receipt is indexed by, in the account or in the admin receipts
+    /// /receipts/{hash}/nonce
+    pub fn nonce(&self) -> Result<Key<u64>> {
+        self.field("nonce")
+    }
+}
+
+/// Keys of the receipts bucket of a level
+/// /receipts_by_level/{level}
+///
+/// The bucket contains the hashes of the receipts stored during this level under /{index}
+/// And the number of receipts under /length
+pub struct LevelReceiptsKeys {
+    level: String,
+}
+
+pub fn level_receipts(level: &u64) -> LevelReceiptsKeys {
+    LevelReceiptsKeys {
+        level: level.to_string(),
+    }
+}
+
+impl LevelReceiptsKeys {
+    /// Path of the whole bucket
+    pub fn root(&self) -> Result<OwnedPath> {
+        path(&["receipts_by_level", &self.level])
+    }
+
+    /// /receipts_by_level/{level}/length
+    pub fn length(&self) -> Result<Key<u64>> {
+        key(&["receipts_by_level", &self.level, "length"])
+    }
+
+    /// Hash of the nth receipt stored during the level
+    /// /receipts_by_level/{level}/{index}
+    pub fn receipt(&self, index: &u64) -> Result<Key<String>> {
+        key(&["receipts_by_level", &self.level, &index.to_string()])
+    }
+}
@@ -0,0 +1,852 @@
+mod keys;
+mod store;
+mod transaction;
+mod value;
+
+use crate::constants;
+use crate::core::hash::Blake2b;
+use crate::core::level::LevelInfo;
+use crate::core::market::{Auction, BestBid, Listing};
+use crate::core::media::Media;
+use crate::core::public_key_hash::PublicKeyHash;
+use crate::core::receipt::{OperationResult, Receipt};
+use crate::core::tweet::{Tweet, TweetState};
+use crate::core::{account::Account, error::*, nonce::Nonce};
+use crypto::hash::ContractKt1Hash;
+use tezos_rollup_encoding::michelson::ticket::TicketHash;
+
+pub use keys::*;
+pub use store::Storage;
+pub use transaction::Transaction;
+pub use value::{Flag, Value};
+
+/// Read the account of the user
+pub fn read_account<S: Storage>(store: &mut S, public_key_hash: PublicKeyHash) -> Result<Account> {
+    let nonce = account(&public_key_hash)
+        .nonce()?
+        .read(store)?
+        .unwrap_or_default();
+    Ok(Account {
+        public_key_hash,
+        nonce: Nonce(nonce),
+    })
+}
+
+/// Store an account to the location /accounts/{tz...}
+///
+/// Only the nonce is stored
+pub fn store_account<'a, S: Storage>(store: &mut S, account: &'a Account) -> Result<&'a Account> {
+    let Account {
+        nonce,
+        public_key_hash,
+    } = account;
+    let () = keys::account(public_key_hash)
+        .nonce()?
+        .write(store, &nonce.0)?;
+    Ok(account)
+}
+
+/// Store a new tweet to the location /tweets/{id}
+///
+/// The tweet is written as one record, its likes counter starts at 0 and it is active
+pub fn store_tweet<'a, S: Storage>(
+    store: &mut S,
+    tweet_id: &u64,
+    tweet: &'a Tweet,
+) -> Result<&'a Tweet> {
+    let keys = keys::tweet(tweet_id);
+    let () = keys.record()?.write(store, tweet)?;
+    let () = keys.likes()?.write(store, &0)?;
+    let () = keys.state()?.write(store, &TweetState::Active)?;
+    Ok(tweet)
+}
+
+/// Delete a tweet and remove it from the indexes of its author and its owner
+pub fn delete_tweet<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<()> {
+    let keys = tweet(tweet_id);
+    let tweet = keys.record()?.read(store)?.ok_or(Error::TweetNotFound)?;
+    let owner = keys.owner()?.read(store)?.ok_or(Error::TweetNotFound)?;
+
+    let () = account(&owner).owned_tweet(tweet_id)?.delete(store)?;
+    let () = account(&tweet.author)
+        .written_tweet(tweet_id)?
+        .delete(store)?;
+    let () = delete_listing(store, tweet_id)?;
+    store.delete(&keys.root()?)
+}
+
+/// Read the state of a tweet at the given level
+///
+/// The transition from collecting to collected is applied lazily:
+/// The new state is written when the finality delay is observed
+pub fn read_tweet_state<S: Storage>(
+    store: &mut S,
+    tweet_id: &u64,
+    level: u32,
+) -> Result<TweetState> {
+    let key = tweet(tweet_id).state()?;
+    let stored_state = key.read(store)?.ok_or(Error::TweetNotFound)?;
+    let state = stored_state.at_level(level);
+    if state != stored_state {
+        let () = key.write(store, &state)?;
+    }
+    Ok(state)
+}
+
+/// Set the state of a tweet
+pub fn set_tweet_state<S: Storage>(
+    store: &mut S,
+    tweet_id: &u64,
+    state: &TweetState,
+) -> Result<()> {
+    tweet(tweet_id).state()?.write(store, state)
+}
+
+/// Increment the tweet counter and return the previous one.
+pub fn increment_tweet_counter<S: Storage>(store: &mut S) -> Result<u64> {
+    let previous_counter = TWEET_COUNTER.read(store)?.unwrap_or_default();
+    let () = TWEET_COUNTER.write(store, &(previous_counter + 1))?;
+    Ok(previous_counter)
+}
+
+/// Read a tweet from the durable state
+///
+/// If the tweet is not present an Option is return
+pub fn read_tweet<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<Option<Tweet>> {
+    tweet(tweet_id).record()?.read(store)
+}
+
+/// Set the royalties of a tweet
+pub fn set_royalties<S: Storage>(store: &mut S, tweet_id: &u64, royalties: &u64) -> Result<()> {
+    tweet(tweet_id).royalties()?.write(store, royalties)
+}
+
+/// Read the royalties of a tweet, there are none by default
+pub fn read_royalties<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<u64> {
+    let royalties = tweet(tweet_id).royalties()?.read(store)?;
+    Ok(royalties.unwrap_or_default())
+}
+
+/// Set the root preimage hash of the body of a long tweet
+pub fn set_body<S: Storage>(store: &mut S, tweet_id: &u64, body: &String) -> Result<()> {
+    tweet(tweet_id).body()?.write(store, body)
+}
+
+/// Store the media attached to a tweet
+pub fn store_media<S: Storage>(store: &mut S, tweet_id: &u64, media: &[Media]) -> Result<()> {
+    let keys = tweet(tweet_id);
+    for (index, media) in media.iter().enumerate() {
+        let () = keys.media(&index)?.write(store, media)?;
+    }
+    Ok(())
+}
+
+/// Read the media attached to a tweet, in the order of the post
+pub fn read_media<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<Vec<Media>> {
+    let keys = tweet(tweet_id);
+    let mut media = Vec::new();
+    for index in 0..constants::MAX_MEDIA_PER_TWEET {
+        match keys.media(&index)?.read(store)? {
+            Some(attachment) => media.push(attachment),
+            None => break,
+        }
+    }
+    Ok(media)
+}
+
+/// Read the number of likes of a tweet
+pub fn read_likes<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<u64> {
+    let likes = tweet(tweet_id).likes()?.read(store)?;
+    Ok(likes.unwrap_or_default())
+}
+
+/// Add one like to a tweet
+///
+/// Only the counter is written, the record of the tweet is untouched
+pub fn increment_likes<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<u64> {
+    let likes = read_likes(store, tweet_id)? + 1;
+    let () = tweet(tweet_id).likes()?.write(store, &likes)?;
+    Ok(likes)
+}
+
+/// Create a flag in the user account that indicates that the user has liked the given tweet
+pub fn set_like_flag<S: Storage>(
+    store: &mut S,
+    public_key_hash: &PublicKeyHash,
+    tweet_id: &u64,
+) -> Result<()> {
+    account(public_key_hash).like(tweet_id)?.write(store, &Flag)
+}
+
+/// Check if the user has a like a tweet
+pub fn is_liked<S: Storage>(
+    store: &mut S,
+    public_key_hash: &PublicKeyHash,
+    tweet_id: &u64,
+) -> Result<bool> {
+    account(public_key_hash).like(tweet_id)?.exists(store)
+}
+
+/// Add a tweet in the "written" path of an account
+pub fn add_written_tweet_to_account<S: Storage>(
+    store: &mut S,
+    public_key_hash: &PublicKeyHash,
+    tweet_id: &u64,
+) -> Result<()> {
+    account(public_key_hash)
+        .written_tweet(tweet_id)?
+        .write(store, &Flag)
+}
+
+/// Read the owner of a tweet
+pub fn read_owner<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<Option<PublicKeyHash>> {
+    tweet(tweet_id).owner()?.read(store)
+}
+
+/// Set the owner of a tweet
+///
+/// The owned index of the account is updated too
+/// Does not remove the tweet from the index of the previous owner, see transfer
+pub fn set_owner<S: Storage>(
+    store: &mut S,
+    tweet_id: &u64,
+    public_key_hash: &PublicKeyHash,
+) -> Result<()> {
+    let () = tweet(tweet_id).owner()?.write(store, public_key_hash)?;
+    account(public_key_hash)
+        .owned_tweet(tweet_id)?
+        .write(store, &Flag)
+}
+
+/// Checks if the user is owner of the tweet
+pub fn is_owner<S: Storage>(
+    store: &mut S,
+    public_key_hash: &PublicKeyHash,
+    tweet_id: &u64,
+) -> Result<()> {
+    let owner = read_owner(store, tweet_id)?.ok_or(Error::TweetNotFound)?;
+
+    match &owner == public_key_hash {
+        true => Ok(()),
+        false => Err(Error::NotOwner),
+    }
+}
+
+/// Transfer a tweet from its owner to another user
+/// Does not check if the sender of the operation owns the tweet
+///
+/// The listing of the tweet is cancelled, the new owner has not put it on sale
+pub fn transfer<S: Storage>(
+    store: &mut S,
+    tweet_id: &u64,
+    destination: &PublicKeyHash,
+) -> Result<()> {
+    let owner = read_owner(store, tweet_id)?.ok_or(Error::TweetNotFound)?;
+    let () = account(&owner).owned_tweet(tweet_id)?.delete(store)?;
+    let () = delete_listing(store, tweet_id)?;
+    set_owner(store, tweet_id, destination)
+}
+
+/// Put a tweet on sale, the previous listing is replaced
+pub fn store_listing<S: Storage>(store: &mut S, tweet_id: &u64, listing: &Listing) -> Result<()> {
+    let keys = keys::listing(tweet_id);
+    let () = keys.ticket()?.write(store, &listing.ticket.to_string())?;
+    keys.price()?.write(store, &listing.price)
+}
+
+/// Read the listing of a tweet
+///
+/// Returns None if the tweet is not on sale
+pub fn read_listing<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<Option<Listing>> {
+    let keys = listing(tweet_id);
+    let ticket = match keys.ticket()?.read(store)? {
+        None => return Ok(None),
+        Some(ticket) => TicketHash::try_from(ticket).map_err(|_| Error::StateDeserializarion)?,
+    };
+    let price = keys.price()?.read(store)?.ok_or(Error::StateDeserializarion)?;
+    Ok(Some(Listing { ticket, price }))
+}
+
+/// Remove a tweet from the market
+pub fn delete_listing<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<()> {
+    store.delete(&listing(tweet_id).root()?)
+}
+
+/// Store an auction, the previous one is replaced
+pub fn store_auction<S: Storage>(store: &mut S, tweet_id: &u64, auction: &Auction) -> Result<()> {
+    let keys = keys::auction(tweet_id);
+    let () = keys.seller()?.write(store, &auction.seller)?;
+    let () = keys.ticket()?.write(store, &auction.ticket.to_string())?;
+    let () = keys.reserve()?.write(store, &auction.reserve)?;
+    let () = keys.end_level()?.write(store, &auction.end_level.into())?;
+    match &auction.best_bid {
+        None => {
+            let () = keys.bidder()?.delete(store)?;
+            keys.bid()?.delete(store)
+        }
+        Some(BestBid { bidder, amount }) => {
+            let () = keys.bidder()?.write(store, bidder)?;
+            keys.bid()?.write(store, amount)
+        }
+    }
+}
+
+/// Read the auction of a tweet
+///
+/// Returns None if the tweet is not auctioned
+pub fn read_auction<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<Option<Auction>> {
+    let keys = auction(tweet_id);
+    let seller = match keys.seller()?.read(store)? {
+        None => return Ok(None),
+        Some(seller) => seller,
+    };
+    let ticket = keys.ticket()?.read(store)?.ok_or(Error::StateDeserializarion)?;
+    let ticket = TicketHash::try_from(ticket).map_err(|_| Error::StateDeserializarion)?;
+    let reserve = keys.reserve()?.read(store)?.ok_or(Error::StateDeserializarion)?;
+    let end_level = keys.end_level()?.read(store)?.ok_or(Error::StateDeserializarion)?;
+    let end_level = u32::try_from(end_level).map_err(|_| Error::StateDeserializarion)?;
+    let bidder = keys.bidder()?.read(store)?;
+    let bid = keys.bid()?.read(store)?;
+    let best_bid = match (bidder, bid) {
+        (Some(bidder), Some(amount)) => Some(BestBid { bidder, amount }),
+        _ => None,
+    };
+    Ok(Some(Auction {
+        seller,
+        ticket,
+        reserve,
+        end_level,
+        best_bid,
+    }))
+}
+
+/// Check if the tweet is auctioned
+pub fn is_auctioned<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<bool> {
+    auction(tweet_id).seller()?.exists(store)
+}
+
+/// Delete the auction of a tweet
+pub fn delete_auction<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<()> {
+    store.delete(&auction(tweet_id).root()?)
+}
+
+/// Add an auction to the bucket of its end level
+///
+/// A bucket holds at most MAX_AUCTIONS_PER_LEVEL auctions
+pub fn add_auction_to_level<S: Storage>(store: &mut S, level: &u64, tweet_id: &u64) -> Result<()> {
+    let keys = level_auctions(level);
+    let length = read_level_auctions_length(store, level)?;
+    if length >= constants::MAX_AUCTIONS_PER_LEVEL {
+        return Err(Error::TooManyAuctions);
+    }
+    let () = keys.auction(&length)?.write(store, tweet_id)?;
+    keys.length()?.write(store, &(length + 1))
+}
+
+/// Read the number of auctions ending at the given level
+pub fn read_level_auctions_length<S: Storage>(store: &mut S, level: &u64) -> Result<u64> {
+    let length = level_auctions(level).length()?.read(store)?;
+    Ok(length.unwrap_or_default())
+}
+
+/// Read the id of the nth tweet whose auction ends at the given level
+pub fn read_level_auction<S: Storage>(store: &mut S, level: &u64, index: &u64) -> Result<u64> {
+    level_auctions(level)
+        .auction(index)?
+        .read(store)?
+        .ok_or(Error::StateDeserializarion)
+}
+
+/// Delete the auctions bucket of a level
+pub fn delete_level_auctions<S: Storage>(store: &mut S, level: &u64) -> Result<()> {
+    store.delete(&level_auctions(level).root()?)
+}
+
+/// Stores a receipt under /receipts/{hash}
+///
+/// The operation field is only present when the message has been decoded
+/// The error field is only present when the operation has failed
+/// The tweet_id field is only present when a tweet has been created
+///
+/// An existing receipt is never overwritten, so that a replayed message cannot change
+/// The outcome of the first one. The receipt of a message rejected before its nonce has been
+/// Consumed is reset when the message is applied, see reset_receipt
+pub fn store_receipt<'a, S: Storage>(store: &mut S, receipt: &'a Receipt) -> Result<&'a Receipt> {
+    let keys = keys::receipt(&receipt.hash().to_string());
+    let position = receipt.position();
+
+    if keys.success()?.exists(store)? {
+        return Ok(receipt);
+    }
+    let stored_level = keys.level()?.read(store)?;
+
+    let () = keys.success()?.write(store, &receipt.success())?;
+    let () = keys.level()?.write(store, &position.level.into())?;
+    let () = keys.index()?.write(store, &position.index.into())?;
+
+    if let Some(operation) = receipt.operation() {
+        let () = keys.operation()?.write(store, &operation.code().into())?;
+    }
+
+    if let Some(code) = receipt.error_code() {
+        let () = keys.error()?.write(store, &code.into())?;
+    }
+
+    if let Some(OperationResult::TweetCreated(tweet_id)) = receipt.operation_result() {
+        let () = keys.tweet_id()?.write(store, tweet_id)?;
+    }
+
+    // A reset receipt is already in the bucket of the level it has been stored in
+    if stored_level != Some(position.level.into()) {
+        let () = add_receipt_to_level(store, &position.level.into(), receipt.hash())?;
+    }
+
+    Ok(receipt)
+}
+
+/// Reset the receipt of a message that is applied for the first time
+///
+/// A previous receipt can only be the one of an attempt rejected before its nonce was consumed,
+/// For instance while the rollup was paused. Its outcome is deleted so that it is replaced
+/// Its level is kept, so that the receipt is not added twice to the bucket of this level
+fn reset_receipt<S: Storage>(store: &mut S, receipt_hash: &str) -> Result<()> {
+    let keys = receipt(receipt_hash);
+    let () = keys.success()?.delete(store)?;
+    let () = keys.operation()?.delete(store)?;
+    let () = keys.error()?.delete(store)?;
+    keys.tweet_id()?.delete(store)
+}
+
+/// Add the hash of a receipt to the bucket of the level it has been stored in
+fn add_receipt_to_level<S: Storage>(
+    store: &mut S,
+    level: &u64,
+    receipt_hash: &Blake2b,
+) -> Result<()> {
+    let keys = level_receipts(level);
+    let length = read_level_receipts_length(store, level)?;
+    let () = keys
+        .receipt(&length)?
+        .write(store, &receipt_hash.to_string())?;
+    keys.length()?.write(store, &(length + 1))
+}
+
+/// Read the number of receipts stored during a level
+pub fn read_level_receipts_length<S: Storage>(store: &mut S, level: &u64) -> Result<u64> {
+    let length = level_receipts(level).length()?.read(store)?;
+    Ok(length.unwrap_or_default())
+}
+
+/// Delete the nth receipt stored during a level
+///
+/// The entry of the receipt in the account or in the admin receipts is deleted as well
+/// The entry of the bucket is kept, the whole bucket is deleted with delete_level_receipts
+///
+/// The receipt is kept if it has been stored again during a later level
+pub fn delete_level_receipt<S: Storage>(store: &mut S, level: &u64, index: &u64) -> Result<()> {
+    let receipt_hash = match level_receipts(level).receipt(index)?.read(store)? {
+        None => return Ok(()),
+        Some(receipt_hash) => receipt_hash,
+    };
+    let keys = receipt(&receipt_hash);
+    match keys.level()?.read(store)? {
+        Some(receipt_level) if receipt_level != *level => return Ok(()),
+        _ => {}
+    }
+    let signer = keys.signer()?.read(store)?;
+    let nonce = keys.nonce()?.read(store)?;
+
+    match (signer, nonce) {
+        (Some(signer), Some(nonce)) => account(&signer).receipt(&Nonce(nonce))?.delete(store)?,
+        (None, Some(nonce)) => admin_receipt(&Nonce(nonce))?.delete(store)?,
+        (_, None) => {}
+    }
+    store.delete(&keys.root()?)
+}
+
+/// Delete the receipts bucket of a level
+pub fn delete_level_receipts<S: Storage>(store: &mut S, level: &u64) -> Result<()> {
+    let path = level_receipts(level).root()?;
+    store.delete(&path)
+}
+
+/// Read the number of levels a receipt is kept
+pub fn read_receipt_retention<S: Storage>(store: &mut S) -> Result<u64> {
+    let retention = RECEIPT_RETENTION.read(store)?;
+    Ok(retention.unwrap_or(constants::RECEIPT_RETENTION))
+}
+
+/// Read the address of the layer 1 contract
+///
+/// Returns None if it has not been configured
+pub fn read_l1_contract<S: Storage>(store: &mut S) -> Result<Option<String>> {
+    L1_CONTRACT.read(store)
+}
+
+/// Read the entrypoint of the layer 1 contract
+pub fn read_l1_entrypoint<S: Storage>(store: &mut S) -> Result<String> {
+    let entrypoint = L1_ENTRYPOINT.read(store)?;
+    Ok(entrypoint.unwrap_or_else(|| constants::L1_TOKEN_CONTRACT_ENTRYPOINT.to_string()))
+}
+
+/// Store the address and the entrypoint of the layer 1 contract
+pub fn store_l1_contract<S: Storage>(
+    store: &mut S,
+    address: &String,
+    entrypoint: &String,
+) -> Result<()> {
+    let () = L1_CONTRACT.write(store, address)?;
+    L1_ENTRYPOINT.write(store, entrypoint)
+}
+
+/// Read the account allowed to sign the admin messages
+///
+/// Returns None if there is no admin
+pub fn read_admin<S: Storage>(store: &mut S) -> Result<Option<PublicKeyHash>> {
+    ADMIN.read(store)
+}
+
+/// Read the nonce of the last admin message
+pub fn read_admin_nonce<S: Storage>(store: &mut S) -> Result<Nonce> {
+    let nonce = ADMIN_NONCE.read(store)?.unwrap_or_default();
+    Ok(Nonce(nonce))
+}
+
+/// Store the nonce of the last admin message
+pub fn store_admin_nonce<S: Storage>(store: &mut S, nonce: &Nonce) -> Result<()> {
+    ADMIN_NONCE.write(store, &nonce.0)
+}
+
+/// Store the number of levels a receipt is kept
+pub fn store_receipt_retention<S: Storage>(store: &mut S, retention: &u64) -> Result<()> {
+    RECEIPT_RETENTION.write(store, retention)
+}
+
+/// Read the layer 1 contract allowed to send admin operations
+///
+/// Returns None if there is no governance contract
+pub fn read_governance<S: Storage>(store: &mut S) -> Result<Option<ContractKt1Hash>> {
+    GOVERNANCE.read(store)
+}
+
+/// Read the address of this rollup
+///
+/// Returns None if it has not been revealed yet
+pub fn read_rollup_address<S: Storage>(store: &mut S) -> Result<Option<String>> {
+    ROLLUP_ADDRESS.read(store)
+}
+
+/// Store the address of this rollup
+pub fn store_rollup_address<S: Storage>(store: &mut S, rollup_address: &String) -> Result<()> {
+    ROLLUP_ADDRESS.write(store, rollup_address)
+}
+
+/// Check if a layer 1 contract is allowed to deposit its tickets on the rollup
+pub fn is_whitelisted_ticketer<S: Storage>(store: &mut S, ticketer: &str) -> Result<bool> {
+    whitelisted_ticketer(ticketer)?.exists(store)
+}
+
+/// Index the receipt of an admin message by its nonce
+///
+/// The message is applied for the first time, a previous receipt is reset
+pub fn add_admin_receipt<S: Storage>(
+    store: &mut S,
+    nonce: &Nonce,
+    receipt_hash: &Blake2b,
+) -> Result<()> {
+    let keys = receipt(&receipt_hash.to_string());
+    let () = reset_receipt(store, &receipt_hash.to_string())?;
+    let () = admin_receipt(nonce)?.write(store, &receipt_hash.to_string())?;
+    keys.nonce()?.write(store, &nonce.0)
+}
+
+/// Pause or resume the processing of the user operations
+pub fn set_paused<S: Storage>(store: &mut S, paused: bool) -> Result<()> {
+    match paused {
+        true => PAUSED.write(store, &Flag),
+        false => PAUSED.delete(store),
+    }
+}
+
+/// Check if the processing of the user operations is paused
+pub fn is_paused<S: Storage>(store: &mut S) -> Result<bool> {
+    PAUSED.exists(store)
+}
+
+/// Grant or revoke the moderator role of an account
+pub fn set_moderator<S: Storage>(
+    store: &mut S,
+    public_key_hash: &PublicKeyHash,
+    is_moderator: bool,
+) -> Result<()> {
+    let key = moderator(public_key_hash)?;
+    match is_moderator {
+        true => key.write(store, &Flag),
+        false => key.delete(store),
+    }
+}
+
+/// Check if an account is a moderator
+pub fn is_moderator<S: Storage>(store: &mut S, public_key_hash: &PublicKeyHash) -> Result<bool> {
+    moderator(public_key_hash)?.exists(store)
+}
+
+/// Read the position of the next receipt to prune
+///
+/// Returns None if the pruning has never been executed
+pub fn read_pruning_cursor<S: Storage>(store: &mut S) -> Result<Option<(u64, u64)>> {
+    let level = PRUNING_LEVEL.read(store)?;
+    let index = PRUNING_INDEX.read(store)?.unwrap_or_default();
+    Ok(level.map(|level| (level, index)))
+}
+
+/// Store the position of the next receipt to prune
+pub fn store_pruning_cursor<S: Storage>(store: &mut S, level: &u64, index: &u64) -> Result<()> {
+    let () = PRUNING_LEVEL.write(store, level)?;
+    PRUNING_INDEX.write(store, index)
+}
+
+/// Read the position of the next auction to settle
+///
+/// Returns None if the settlement has never been executed
+pub fn read_settlement_cursor<S: Storage>(store: &mut S) -> Result<Option<(u64, u64)>> {
+    let level = SETTLEMENT_LEVEL.read(store)?;
+    let index = SETTLEMENT_INDEX.read(store)?.unwrap_or_default();
+    Ok(level.map(|level| (level, index)))
+}
+
+/// Store the position of the next auction to settle
+pub fn store_settlement_cursor<S: Storage>(store: &mut S, level: &u64, index: &u64) -> Result<()> {
+    let () = SETTLEMENT_LEVEL.write(store, level)?;
+    SETTLEMENT_INDEX.write(store, index)
+}
+
+/// Index the receipt of an operation in the account that has signed it
+///
+/// It makes possible for a client to retrieve the history of its operations
+/// The receipt keeps the signer and the nonce, so that the entry is pruned with the receipt
+///
+/// The message is applied for the first time, a previous receipt is reset
+pub fn add_receipt_to_account<S: Storage>(
+    store: &mut S,
+    public_key_hash: &PublicKeyHash,
+    nonce: &Nonce,
+    receipt_hash: &Blake2b,
+) -> Result<()> {
+    let receipt_hash = receipt_hash.to_string();
+    let keys = receipt(&receipt_hash);
+    let () = reset_receipt(store, &receipt_hash)?;
+    let () = account(public_key_hash)
+        .receipt(nonce)?
+        .write(store, &receipt_hash)?;
+    let () = keys.signer()?.write(store, public_key_hash)?;
+    keys.nonce()?.write(store, &nonce.0)
+}
+
+/// Set the block when the tweet has been collected
+pub fn set_collected_block<S: Storage>(
+    store: &mut S,
+    tweet_id: &u64,
+    previous_block: &str,
+) -> Result<()> {
+    tweet(tweet_id)
+        .collected_hash()?
+        .write(store, &previous_block.to_string())
+}
+
+/// Remove the block when the tweet has been collected
+pub fn delete_collected_block<S: Storage>(store: &mut S, tweet_id: &u64) -> Result<()> {
+    tweet(tweet_id).collected_hash()?.delete(store)
+}
+
+/// Indicates that a tweet is beeing collected by the given user
+pub fn add_collecting_tweet_to_account<S: Storage>(
+    store: &mut S,
+    public_key_hash: &PublicKeyHash,
+    tweet_id: &u64,
+) -> Result<()> {
+    account(public_key_hash)
+        .collecting(tweet_id)?
+        .write(store, &Flag)
+}
+
+/// Indicates that a tweet is not collected anymore by the given user
+pub fn remove_collecting_tweet_from_account<S: Storage>(
+    store: &mut S,
+    public_key_hash: &PublicKeyHash,
+    tweet_id: &u64,
+) -> Result<()> {
+    account(public_key_hash).collecting(tweet_id)?.delete(store)
+}
+
+/// Read the amount of tickets owned by the user
+pub fn read_balance<S: Storage>(
+    store: &mut S,
+    public_key_hash: &PublicKeyHash,
+    ticket: &TicketHash,
+) -> Result<u64> {
+    let balance = account(public_key_hash).balance(ticket)?.read(store)?;
+    Ok(balance.unwrap_or_default())
+}
+
+/// Add tickets to the balance of the user
+///
+/// Returns the new balance
+pub fn credit_balance<S: Storage>(
+    store: &mut S,
+    public_key_hash: &PublicKeyHash,
+    ticket: &TicketHash,
+    amount: &u64,
+) -> Result<u64> {
+    let balance = read_balance(store, public_key_hash, ticket)?
+        .checked_add(*amount)
+        .ok_or(Error::BalanceOverflow)?;
+    let () = account(public_key_hash)
+        .balance(ticket)?
+        .write(store, &balance)?;
+    Ok(balance)
+}
+
+/// Remove tickets from the balance of the user
+///
+/// Returns the new balance
+pub fn debit_balance<S: Storage>(
+    store: &mut S,
+    public_key_hash: &PublicKeyHash,
+    ticket: &TicketHash,
+    amount: &u64,
+) -> Result<u64> {
+    let balance = read_balance(store, public_key_hash, ticket)?
+        .checked_sub(*amount)
+        .ok_or(Error::InsufficientBalance)?;
+    let () = account(public_key_hash)
+        .balance(ticket)?
+        .write(store, &balance)?;
+    Ok(balance)
+}
+
+/// Add a tip to the totals of the tweet and of its author
+pub fn add_tip<S: Storage>(
+    store: &mut S,
+    tweet_id: &u64,
+    author: &PublicKeyHash,
+    ticket: &TicketHash,
+    amount: &u64,
+) -> Result<()> {
+    let tweet_tips = tweet(tweet_id).tips(ticket)?;
+    let total = tweet_tips.read(store)?.unwrap_or_default();
+    let total = total.checked_add(*amount).ok_or(Error::BalanceOverflow)?;
+    let () = tweet_tips.write(store, &total)?;
+
+    let author_tips = account(author).tips(ticket)?;
+    let total = author_tips.read(store)?.unwrap_or_default();
+    let total = total.checked_add(*amount).ok_or(Error::BalanceOverflow)?;
+    author_tips.write(store, &total)
+}
+
+/// Remember which ticketer has created the ticket
+pub fn store_ticketer<S: Storage>(
+    store: &mut S,
+    ticket: &TicketHash,
+    ticketer: &str,
+) -> Result<()> {
+    keys::ticket(ticket)
+        .ticketer()?
+        .write(store, &ticketer.to_string())
+}
+
+/// Read the ticketer of a ticket
+///
+/// Returns None if the ticket has never been deposited
+pub fn read_ticketer<S: Storage>(store: &mut S, ticket: &TicketHash) -> Result<Option<String>> {
+    keys::ticket(ticket).ticketer()?.read(store)
+}
+
+/// Store the information of the level being processed
+///
+/// They are needed to resume the processing of the inbox after a reboot
+pub fn store_level_info<S: Storage>(store: &mut S, level_info: &LevelInfo) -> Result<()> {
+    let () = INBOX_LEVEL.write(store, &level_info.level.into())?;
+    INBOX_PREVIOUS_HASH.write(store, &level_info.previous_hash)
+}
+
+/// Read the information of the level being processed
+pub fn read_stored_level_info<S: Storage>(store: &mut S) -> Result<LevelInfo> {
+    let level = INBOX_LEVEL
+        .read(store)?
+        .ok_or(Error::StateDeserializarion)?;
+    let level = u32::try_from(level).map_err(|_| Error::StateDeserializarion)?;
+    let previous_hash = INBOX_PREVIOUS_HASH
+        .read(store)?
+        .ok_or(Error::StateDeserializarion)?;
+    Ok(LevelInfo {
+        level,
+        previous_hash,
+    })
+}
+
+/// Schedule the installation of a new kernel
+///
+/// The installation of a previously scheduled kernel is cancelled
+pub fn store_pending_upgrade<S: Storage>(store: &mut S, root_hash: &String) -> Result<()> {
+    let () = store.delete(&UPGRADE)?;
+    PENDING_UPGRADE.write(store, root_hash)
+}
+
+/// Returns the root hash of the kernel being installed, if any
+pub fn read_pending_upgrade<S: Storage>(store: &mut S) -> Result<Option<String>> {
+    PENDING_UPGRADE.read(store)
+}
+
+/// Returns true if a kernel is being installed
+pub fn is_upgrade_pending<S: Storage>(store: &mut S) -> Result<bool> {
+    PENDING_UPGRADE.exists(store)
+}
+
+/// Read the progress of the kernel upgrade
+///
+/// Returns the length of the stack of pages left to reveal,
+/// The number of revealed pages and the number of written bytes
+/// Or None if the revelation of the pages has not started yet
+pub fn read_upgrade_progress<S: Storage>(store: &mut S) -> Result<Option<(u64, u64, u64)>> {
+    let stack_length = UPGRADE_STACK_LENGTH.read(store)?;
+    let pages = UPGRADE_PAGES.read(store)?.unwrap_or_default();
+    let size = UPGRADE_SIZE.read(store)?.unwrap_or_default();
+    Ok(stack_length.map(|stack_length| (stack_length, pages, size)))
+}
+
+/// Store the progress of the kernel upgrade
+pub fn store_upgrade_progress<S: Storage>(
+    store: &mut S,
+    stack_length: &u64,
+    pages: &u64,
+    size: &u64,
+) -> Result<()> {
+    let () = UPGRADE_STACK_LENGTH.write(store, stack_length)?;
+    let () = UPGRADE_PAGES.write(store, pages)?;
+    UPGRADE_SIZE.write(store, size)
+}
+
+/// Delete the state of the kernel upgrade, so that it is not attempted again
+pub fn delete_upgrade<S: Storage>(store: &mut S) -> Result<()> {
+    store.delete(&UPGRADE)
+}
+
+/// Ask the host to reboot the kernel
+///
+/// The next run will resume the processing of the current inbox
+pub fn request_reboot<S: Storage>(store: &mut S) -> Result<()> {
+    let () = INBOX_RESUME.write(store, &Flag)?;
+    REBOOT.write(store, &Flag)
+}
+
+/// Returns true if the kernel has been rebooted in the middle of an inbox
+///
+/// The flag is removed, so that the next level starts from the beginning
+pub fn take_resume_flag<S: Storage>(store: &mut S) -> Result<bool> {
+    let is_resuming = INBOX_RESUME.exists(store)?;
+    if is_resuming {
+        let () = INBOX_RESUME.delete(store)?;
+    }
+    Ok(is_resuming)
+}
@@ -0,0 +1,61 @@
+use crate::core::error::*;
+use host::path::Path;
+use host::runtime::load_value_sized;
+use host::{rollup_core::RawRollupCore, runtime::Runtime};
+
+/// The durable state, from the point of view of the kernel operations
+///
+/// It is implemented by the host, which reads and writes directly in the durable state,
+/// And by a Transaction, which keeps its writes in a scratch subtree until it is committed
+pub trait Storage {
+    /// Check if a path exists
+    fn has(&mut self, path: &impl Path) -> Result<bool>;
+
+    /// Read the value stored at a given path
+    fn read(&mut self, path: &impl Path) -> Result<Option<Vec<u8>>>;
+
+    /// Write a value at a given path
+    fn write(&mut self, path: &impl Path, data: &[u8]) -> Result<()>;
+
+    /// Delete a path and all its subpaths
+    fn delete(&mut self, path: &impl Path) -> Result<()>;
+
+    /// Write a message to the outbox
+    fn output(&mut self, data: &[u8]) -> Result<()>;
+}
+
+impl<Host: RawRollupCore + Runtime> Storage for Host {
+    fn has(&mut self, path: &impl Path) -> Result<bool> {
+        let exists = Runtime::store_has(self, path)?
+            .map(|_| true)
+            .unwrap_or_default();
+        Ok(exists)
+    }
+
+    fn read(&mut self, path: &impl Path) -> Result<Option<Vec<u8>>> {
+        let is_exists = self.has(path)?;
+        if !is_exists {
+            return Ok(None);
+        }
+
+        let buffer = load_value_sized(self, path).map_err(Error::from)?;
+        Ok(Some(buffer))
+    }
+
+    fn write(&mut self, path: &impl Path, data: &[u8]) -> Result<()> {
+        self.store_write(path, data, 0).map_err(Error::from)
+    }
+
+    fn delete(&mut self, path: &impl Path) -> Result<()> {
+        let is_exists = self.has(path)?;
+        if !is_exists {
+            return Ok(());
+        }
+
+        self.store_delete(path).map_err(Error::from)
+    }
+
+    fn output(&mut self, data: &[u8]) -> Result<()> {
+        self.write_output(data).map_err(Error::from)
+    }
+}
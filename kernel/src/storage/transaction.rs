@@ -0,0 +1,249 @@
+use crate::core::error::*;
+use crate::storage::Storage;
+use host::path::{concat, OwnedPath, Path, RefPath};
+use host::{rollup_core::RawRollupCore, runtime::Runtime};
+
+const TRANSACTION: RefPath = RefPath::assert_from(b"/tx");
+
+/// Atomic set of changes to the durable state
+///
+/// The values are written under /tx/{path} and are moved to {path} when the transaction is committed
+/// The deletions and the outbox messages are kept in memory and applied when the transaction is committed
+/// The outbox messages are written first, if one of them is refused the durable state is unchanged
+/// If the transaction is rolled back, the scratch subtree is deleted and nothing is applied
+pub struct Transaction<'a, Host: RawRollupCore + Runtime> {
+    host: &'a mut Host,
+    written: Vec<Vec<u8>>,
+    deleted: Vec<Vec<u8>>,
+    outputs: Vec<Vec<u8>>,
+}
+
+/// Returns true if the path is equal to the prefix or is a subpath of the prefix
+fn is_subpath(path: &[u8], prefix: &[u8]) -> bool {
+    path == prefix || (path.starts_with(prefix) && path.get(prefix.len()) == Some(&b'/'))
+}
+
+impl<'a, Host: RawRollupCore + Runtime> Transaction<'a, Host> {
+    /// Starts a new transaction
+    pub fn new(host: &'a mut Host) -> Self {
+        Transaction {
+            host,
+            written: Vec::default(),
+            deleted: Vec::default(),
+            outputs: Vec::default(),
+        }
+    }
+
+    /// Path where a value is written until the transaction is committed
+    fn scratch_path(path: &impl Path) -> Result<OwnedPath> {
+        concat(&TRANSACTION, path).map_err(Error::from)
+    }
+
+    fn is_written(&self, path: &[u8]) -> bool {
+        self.written
+            .iter()
+            .any(|written| written.as_slice() == path)
+    }
+
+    fn is_deleted(&self, path: &[u8]) -> bool {
+        self.deleted
+            .iter()
+            .any(|deleted| is_subpath(path, deleted.as_slice()))
+    }
+
+    /// Applies all the changes of the transaction to the durable state
+    pub fn commit(self) -> Result<()> {
+        let Transaction {
+            host,
+            written,
+            deleted,
+            outputs,
+        } = self;
+
+        // The outputs are emitted before any change is applied to the durable state
+        // So that an outbox failure aborts the whole transaction
+        for output in outputs {
+            if let Err(err) = host.output(&output) {
+                let () = host.delete(&TRANSACTION)?;
+                return Err(err);
+            }
+        }
+        for path in deleted {
+            let path = OwnedPath::try_from(path).map_err(Error::from)?;
+            let () = host.delete(&path)?;
+        }
+        for path in written {
+            let path = OwnedPath::try_from(path).map_err(Error::from)?;
+            let scratch_path = Self::scratch_path(&path)?;
+            let () = host.store_move(&scratch_path, &path).map_err(Error::from)?;
+        }
+        host.delete(&TRANSACTION)
+    }
+
+    /// Discards all the changes of the transaction
+    pub fn rollback(self) -> Result<()> {
+        self.host.delete(&TRANSACTION)
+    }
+
+    /// Deletes the scratch subtree left by a transaction that has not been completed
+    ///
+    /// It happens when a commit fails half way, the changes that have not been applied are lost
+    pub fn clean_up(host: &mut Host) -> Result<()> {
+        host.delete(&TRANSACTION)
+    }
+
+    /// Runs an operation in a new transaction
+    ///
+    /// The changes are committed if the operation succeeds, and discarded otherwise
+    pub fn run<A>(
+        host: &'a mut Host,
+        operation: impl FnOnce(&mut Transaction<'a, Host>) -> Result<A>,
+    ) -> Result<A> {
+        let mut transaction = Transaction::new(host);
+        match operation(&mut transaction) {
+            Ok(result) => {
+                let () = transaction.commit()?;
+                Ok(result)
+            }
+            Err(err) => {
+                let () = transaction.rollback()?;
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<'a, Host: RawRollupCore + Runtime> Storage for Transaction<'a, Host> {
+    fn has(&mut self, path: &impl Path) -> Result<bool> {
+        let bytes = path.as_bytes();
+        let is_written = self
+            .written
+            .iter()
+            .any(|written| is_subpath(written.as_slice(), bytes));
+
+        if is_written {
+            Ok(true)
+        } else if self.is_deleted(bytes) {
+            Ok(false)
+        } else {
+            self.host.has(path)
+        }
+    }
+
+    fn read(&mut self, path: &impl Path) -> Result<Option<Vec<u8>>> {
+        let bytes = path.as_bytes();
+        if self.is_written(bytes) {
+            let scratch_path = Self::scratch_path(path)?;
+            self.host.read(&scratch_path)
+        } else if self.is_deleted(bytes) {
+            Ok(None)
+        } else {
+            self.host.read(path)
+        }
+    }
+
+    fn write(&mut self, path: &impl Path, data: &[u8]) -> Result<()> {
+        let scratch_path = Self::scratch_path(path)?;
+        let () = self.host.write(&scratch_path, data)?;
+
+        let bytes = path.as_bytes();
+        if !self.is_written(bytes) {
+            self.written.push(bytes.to_vec());
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, path: &impl Path) -> Result<()> {
+        let scratch_path = Self::scratch_path(path)?;
+        let () = self.host.delete(&scratch_path)?;
+
+        let bytes = path.as_bytes();
+        self.written
+            .retain(|written| !is_subpath(written.as_slice(), bytes));
+        self.deleted.push(bytes.to_vec());
+        Ok(())
+    }
+
+    fn output(&mut self, data: &[u8]) -> Result<()> {
+        self.outputs.push(data.to_vec());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use host::path::RefPath;
+    use mock_runtime::{host::MockHost, state::HostState};
+
+    use crate::storage::{Key, Storage, Transaction};
+
+    const VALUE: Key<u64, RefPath> = Key::new(RefPath::assert_from(b"/value"));
+
+    #[test]
+    fn test_commit_transaction() {
+        let mut host = MockHost::from(HostState::default());
+
+        let mut transaction = Transaction::new(&mut host);
+        let () = VALUE.write(&mut transaction, &42).unwrap();
+        assert_eq!(VALUE.read(&mut transaction).unwrap(), Some(42));
+        transaction.commit().unwrap();
+
+        assert_eq!(VALUE.read(&mut host).unwrap(), Some(42));
+    }
+
+    #[test]
+    fn test_rollback_transaction() {
+        let mut host = MockHost::from(HostState::default());
+        let () = VALUE.write(&mut host, &1).unwrap();
+
+        let mut transaction = Transaction::new(&mut host);
+        let () = VALUE.write(&mut transaction, &42).unwrap();
+        transaction.output(&[0x00]).unwrap();
+        transaction.rollback().unwrap();
+
+        assert_eq!(VALUE.read(&mut host).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_failed_output_aborts_transaction() {
+        let mut host = MockHost::from(HostState::default());
+        let () = VALUE.write(&mut host, &1).unwrap();
+
+        // The outbox does not accept messages larger than 4096 bytes
+        let mut transaction = Transaction::new(&mut host);
+        let () = VALUE.write(&mut transaction, &42).unwrap();
+        transaction.output(&[0x00; 4097]).unwrap();
+        assert!(transaction.commit().is_err());
+
+        assert_eq!(VALUE.read(&mut host).unwrap(), Some(1));
+        assert!(!host.has(&RefPath::assert_from(b"/tx")).unwrap());
+    }
+
+    #[test]
+    fn test_delete_in_transaction() {
+        let mut host = MockHost::from(HostState::default());
+        let () = VALUE.write(&mut host, &1).unwrap();
+
+        let mut transaction = Transaction::new(&mut host);
+        let () = VALUE.delete(&mut transaction).unwrap();
+        assert!(!VALUE.exists(&mut transaction).unwrap());
+        transaction.commit().unwrap();
+
+        assert!(!VALUE.exists(&mut host).unwrap());
+    }
+
+    #[test]
+    fn test_clean_up_incomplete_transaction() {
+        let mut host = MockHost::from(HostState::default());
+
+        // The transaction is dropped without being committed or rolled back
+        let mut transaction = Transaction::new(&mut host);
+        let () = VALUE.write(&mut transaction, &42).unwrap();
+        drop(transaction);
+        assert!(host.has(&RefPath::assert_from(b"/tx/value")).unwrap());
+
+        Transaction::clean_up(&mut host).unwrap();
+        assert!(!host.has(&RefPath::assert_from(b"/tx")).unwrap());
+        assert!(!VALUE.exists(&mut host).unwrap());
+    }
+}
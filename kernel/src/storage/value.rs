@@ -0,0 +1,221 @@
+use crate::core::error::*;
+use crate::core::media::Media;
+use crate::core::public_key_hash::PublicKeyHash;
+use crate::core::tweet::{Tweet, TweetState};
+use crypto::hash::ContractKt1Hash;
+
+/// Size of an encoded public key hash: a tag followed by a 20 bytes hash
+const PUBLIC_KEY_HASH_SIZE: usize = 21;
+
+/// A value that can be stored in the durable state
+///
+/// Each value knows how to encode and decode itself
+pub trait Value: Sized {
+    /// Encode the value to the bytes written in the durable state
+    fn encode(&self) -> Vec<u8>;
+
+    /// Decode the value from the bytes read from the durable state
+    fn decode(bytes: Vec<u8>) -> Result<Self>;
+}
+
+/// A value without content, only its presence matters
+pub struct Flag;
+
+impl Value for Flag {
+    fn encode(&self) -> Vec<u8> {
+        vec![0x00]
+    }
+
+    fn decode(_: Vec<u8>) -> Result<Self> {
+        Ok(Flag)
+    }
+}
+
+/// Encoded as 8 bytes in big endian
+impl Value for u64 {
+    fn encode(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        let bytes: [u8; 8] = bytes.try_into().map_err(|_| Error::StateDeserializarion)?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+}
+
+/// Encoded as one byte, 0x01 for true and 0x00 for false
+impl Value for bool {
+    fn encode(&self) -> Vec<u8> {
+        match self {
+            true => vec![0x01],
+            false => vec![0x00],
+        }
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        match bytes.as_slice() {
+            [0x01] => Ok(true),
+            [0x00] => Ok(false),
+            _ => Err(Error::StateDeserializarion),
+        }
+    }
+}
+
+/// Encoded as UTF-8
+impl Value for String {
+    fn encode(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        String::from_utf8(bytes).map_err(Error::from)
+    }
+}
+
+/// Encoded as its base58 representation
+impl Value for PublicKeyHash {
+    fn encode(&self) -> Vec<u8> {
+        self.to_string().encode()
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        let str = String::decode(bytes)?;
+        PublicKeyHash::from_b58(&str)
+    }
+}
+
+/// Encoded as its base58 representation
+impl Value for ContractKt1Hash {
+    fn encode(&self) -> Vec<u8> {
+        self.to_b58check().encode()
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        let str = String::decode(bytes)?;
+        ContractKt1Hash::from_b58check(&str).map_err(|_| Error::StateDeserializarion)
+    }
+}
+
+/// Encoded as one record: the author in binary, followed by the content in UTF-8
+///
+/// The likes are not part of the record, they are stored apart
+impl Value for Tweet {
+    fn encode(&self) -> Vec<u8> {
+        [self.author.to_bytes(), self.content.encode()].concat()
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        if bytes.len() < PUBLIC_KEY_HASH_SIZE {
+            return Err(Error::StateDeserializarion);
+        }
+        let (author, content) = bytes.split_at(PUBLIC_KEY_HASH_SIZE);
+        let author = PublicKeyHash::from_bytes(author)?;
+        let content = String::decode(content.to_vec())?;
+        Ok(Tweet { author, content })
+    }
+}
+
+/// Encoded as one record: the size on 8 bytes, the MIME type prefixed by its length on 1 byte,
+/// And the hash in UTF-8
+impl Value for Media {
+    fn encode(&self) -> Vec<u8> {
+        let mime_type = self.mime_type.encode();
+        [
+            self.size.encode(),
+            vec![mime_type.len() as u8],
+            mime_type,
+            self.hash.encode(),
+        ]
+        .concat()
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        if bytes.len() < 9 {
+            return Err(Error::StateDeserializarion);
+        }
+        let (size, rest) = bytes.split_at(8);
+        let (mime_type_size, rest) = (rest[0] as usize, &rest[1..]);
+        if rest.len() < mime_type_size {
+            return Err(Error::StateDeserializarion);
+        }
+        let (mime_type, hash) = rest.split_at(mime_type_size);
+        Ok(Media {
+            mime_type: String::decode(mime_type.to_vec())?,
+            size: u64::decode(size.to_vec())?,
+            hash: String::decode(hash.to_vec())?,
+        })
+    }
+}
+
+/// Encoded as a tag, followed by the level of the collect for a collecting tweet
+impl Value for TweetState {
+    fn encode(&self) -> Vec<u8> {
+        match self {
+            TweetState::Active => vec![0x00],
+            TweetState::Collecting(level) => [&[0x01], level.to_be_bytes().as_slice()].concat(),
+            TweetState::Collected => vec![0x02],
+        }
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        match bytes.as_slice() {
+            [0x00] => Ok(TweetState::Active),
+            [0x01, level @ ..] => {
+                let level: [u8; 4] = level.try_into().map_err(|_| Error::StateDeserializarion)?;
+                Ok(TweetState::Collecting(u32::from_be_bytes(level)))
+            }
+            [0x02] => Ok(TweetState::Collected),
+            _ => Err(Error::StateDeserializarion),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+    use crate::core::media::Media;
+    use crate::core::public_key_hash::PublicKeyHash;
+    use crate::core::tweet::Tweet;
+
+    #[test]
+    fn test_u64_roundtrip() {
+        let bytes = 42u64.encode();
+        assert_eq!(bytes.len(), 8);
+        assert_eq!(u64::decode(bytes).unwrap(), 42);
+    }
+
+    #[test]
+    fn test_media_roundtrip() {
+        let media = Media {
+            mime_type: "image/png".to_string(),
+            size: 1024,
+            hash: "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string(),
+        };
+        let bytes = media.encode();
+        assert_eq!(bytes.len(), 8 + 1 + 9 + 46);
+
+        let result = Media::decode(bytes).unwrap();
+        assert_eq!(result.mime_type, media.mime_type);
+        assert_eq!(result.size, media.size);
+        assert_eq!(result.hash, media.hash);
+    }
+
+    #[test]
+    fn test_malformed_u64() {
+        assert!(u64::decode(vec![0x00]).is_err());
+    }
+
+    #[test]
+    fn test_tweet_roundtrip() {
+        let tweet = Tweet {
+            author: PublicKeyHash::from_b58("tz1QFD9WqLWZmmAuqnnTPPUjfauitYEWdshv").unwrap(),
+            content: "Hello world".to_string(),
+        };
+        let bytes = tweet.encode();
+        assert_eq!(bytes.len(), 21 + 11);
+
+        let result = Tweet::decode(bytes).unwrap();
+        assert_eq!(result.author.to_string(), tweet.author.to_string());
+        assert_eq!(result.content, tweet.content);
+    }
+}
@@ -0,0 +1,107 @@
+//! Tick costs of the stages of a kernel run
+//!
+//! The costs are guesses, not benchmarks: only READ_INPUT_TICKS has been measured
+//! The other costs are derived from it by counting the accesses to the durable state
+//! And the revealed pages of each stage, they can be off by a large factor
+//! That is why half of a run is kept as a safety margin
+//! Each cost must be replaced by a benchmark of its stage once profiled with the wasm debugger
+
+use crate::constants::MAX_PRUNED_RECEIPTS_PER_LEVEL;
+
+/// Maximum number of ticks of a kernel run
+pub const MAX_TICKS: u64 = 11_000_000_000;
+
+/// Number of ticks kept aside for the errors of the estimates and for the end of the run
+///
+/// The estimated costs are guesses, so half of the run is kept aside:
+/// The run stays under MAX_TICKS as long as the real costs are less than twice the estimates
+pub const SAFETY_MARGIN_TICKS: u64 = MAX_TICKS / 2;
+
+/// Reading one message from the inbox
+/// Benchmark: 2_000_000 ticks (see read_input)
+pub const READ_INPUT_TICKS: u64 = 2_000_000;
+
+/// One read, write or deletion in the durable state, with its existence check
+/// Derivation: half of READ_INPUT_TICKS, which is one host call copying up to 4KB
+/// And the decoding of the message
+pub const STORAGE_ACCESS_TICKS: u64 = READ_INPUT_TICKS / 2;
+
+/// Reading the StartOfLevel and InfoPerLevel messages, and pruning the old receipts
+/// Derivation: 2 inbox reads, 4 accesses for the level and the pruning cursor,
+/// And 5 accesses per pruned receipt (entry of the bucket, signer, nonce and 2 deletions)
+pub const START_OF_LEVEL_TICKS: u64 =
+    2 * READ_INPUT_TICKS + (4 + 5 * MAX_PRUNED_RECEIPTS_PER_LEVEL) * STORAGE_ACCESS_TICKS;
+
+/// Settling one ended auction, it is also charged for each visited bucket
+/// Derivation: 25 accesses, reading and deleting the auction (8), paying the seller
+/// And the author (6), transferring the tweet (5) and committing the transaction (6)
+pub const SETTLE_AUCTION_TICKS: u64 = 25 * STORAGE_ACCESS_TICKS;
+
+/// Revealing one page of a new kernel
+/// Derivation: the page is revealed like an inbox message is read, then 3 accesses
+/// To pop its hash and to write its content or push its hashes
+pub const KERNEL_PAGE_TICKS: u64 = READ_INPUT_TICKS + 3 * STORAGE_ACCESS_TICKS;
+
+/// Verifying the signature of a message
+/// Not measured: hashing of the inner message and verification of an ed25519 signature,
+/// The computation of a message that has not been profiled yet, 25 times READ_INPUT_TICKS
+pub const VERIFY_SIGNATURE_TICKS: u64 = 25 * READ_INPUT_TICKS;
+
+/// Applying the operation of a message
+/// Derivation: 30 accesses for the most expensive operation, buying a tweet
+/// (nonce, listing, state, royalties, 3 balances, transfer and commit of the transaction)
+pub const APPLY_OPERATION_TICKS: u64 = 30 * STORAGE_ACCESS_TICKS;
+
+/// Revealing the body of a long tweet
+/// Derivation: the MAX_LONG_TWEET_SIZE bytes of content are 10 pages of 4KB, each revealed
+/// Like an inbox message is read (20_000_000), the rest covers hashing the pages, not measured
+pub const REVEAL_LONG_TWEET_TICKS: u64 = 50 * READ_INPUT_TICKS;
+
+/// Storing the receipt of a message
+/// Derivation: 6 writes of the receipt fields and 3 accesses to the bucket of the level
+pub const STORE_RECEIPT_TICKS: u64 = 9 * STORAGE_ACCESS_TICKS;
+
+/// Worst case of processing one message of the inbox
+pub const MESSAGE_TICKS: u64 = READ_INPUT_TICKS
+    + VERIFY_SIGNATURE_TICKS
+    + REVEAL_LONG_TWEET_TICKS
+    + APPLY_OPERATION_TICKS
+    + STORE_RECEIPT_TICKS;
+
+/// Estimates the number of ticks consumed by the kernel during a run
+///
+/// The host does not expose the number of consumed ticks
+/// So each stage adds its estimated cost to the counter
+#[derive(Default)]
+pub struct TickCounter {
+    consumed: u64,
+}
+
+impl TickCounter {
+    /// Add the cost of a stage to the counter
+    pub fn consume(&mut self, ticks: u64) {
+        self.consumed = self.consumed.saturating_add(ticks);
+    }
+
+    /// Returns true if the given number of ticks can still be consumed during this run
+    pub fn has_budget_for(&self, ticks: u64) -> bool {
+        self.consumed.saturating_add(ticks) <= MAX_TICKS - SAFETY_MARGIN_TICKS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TickCounter, MAX_TICKS, MESSAGE_TICKS, SAFETY_MARGIN_TICKS};
+
+    #[test]
+    fn test_budget() {
+        let mut ticks = TickCounter::default();
+        assert!(ticks.has_budget_for(MESSAGE_TICKS));
+
+        ticks.consume(MAX_TICKS - SAFETY_MARGIN_TICKS - MESSAGE_TICKS);
+        assert!(ticks.has_budget_for(MESSAGE_TICKS));
+
+        ticks.consume(1);
+        assert!(!ticks.has_budget_for(MESSAGE_TICKS));
+    }
+}
@@ -0,0 +1,35 @@
+// SPDX-FileCopyrightText: 2023 Marigold <contact@marigold.dev>
+//
+// SPDX-License-Identifier: MIT
+
+//! Splits a file into DAC pages, writes them to `<dir>/<hex hash>` and prints
+//! the root hash.
+//!
+//! Usage: `preimages <file> <dir>`
+
+use std::path::Path;
+use std::process::exit;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let (file, dir) = match args.as_slice() {
+        [_, file, dir] => (file, dir),
+        _ => {
+            eprintln!("Usage: preimages <file> <dir>");
+            exit(1)
+        }
+    };
+
+    let content = std::fs::read(file).unwrap_or_else(|err| {
+        eprintln!("Cannot read {}: {}", file, err);
+        exit(1)
+    });
+
+    match tezos_rollup_encoding::dac::write_preimages(&content, Path::new(dir)) {
+        Ok(root_hash) => println!("{}", hex::encode(root_hash.as_ref())),
+        Err(err) => {
+            eprintln!("Cannot write the preimages to {}: {}", dir, err);
+            exit(1)
+        }
+    }
+}
@@ -32,6 +32,7 @@
 //! Merkle tree encodings of DAC pages are versioned, to allow for multiple
 //! hashing schemes to be used.
 
+use host::rollup_core::PREIMAGE_HASH_SIZE;
 use host::runtime::{Runtime, RuntimeError};
 
 /// Maximum size of dac pages is 4Kb.
@@ -49,7 +50,8 @@ pub(crate) const MAX_USABLE_PAGE_SIZE: usize =
 
 #[cfg(feature = "alloc")]
 pub use encoding::{prepare_preimages, Page, PreimageHash, V0ContentPage, V0HashPage};
-use tezos_smart_rollup_core::PREIMAGE_HASH_SIZE;
+#[cfg(all(feature = "alloc", not(target_arch = "wasm32")))]
+pub use encoding::write_preimages;
 
 /// Hashes `content` into a preimage hash.
 ///
@@ -77,7 +79,6 @@ mod encoding {
     use tezos_data_encoding::enc::BinWriter;
     use tezos_data_encoding::encoding::HasEncoding;
     use tezos_data_encoding::nom::NomReader;
-    use tezos_smart_rollup_core::PREIMAGE_HASH_SIZE;
 
     /// A Dac page: either a leaf node of contents, or node of hashes.
     #[derive(Debug, HasEncoding, NomReader, BinWriter)]
@@ -171,48 +172,80 @@ mod encoding {
     }
 
     /// Generates the preimages of the given content.
+    ///
+    /// `handle` is called with each encoded page and its hash, from the content pages
+    /// up to the root page.
     pub fn prepare_preimages(
         content: &[u8],
         mut handle: impl FnMut(PreimageHash, Vec<u8>),
     ) -> Result<PreimageHash, crypto::blake2b::Blake2bError> {
         let mut hashes = Vec::new();
 
-        for chunk in content.chunks(V0ContentPage::MAX_CONTENT_SIZE) {
-            let page = Page::V0ContentPage(V0ContentPage {
-                contents: chunk.to_vec(),
-            });
-            let mut encoded = Vec::new();
-            page.bin_write(&mut encoded).unwrap();
-
-            let hash = make_preimage_hash(&encoded)?.into();
-            let hash = PreimageHash { hash };
-
-            hashes.push(hash.clone());
-
-            handle(hash, encoded);
+        for page in V0ContentPage::new_pages(content) {
+            let hash = hash_page(Page::V0ContentPage(page), &mut handle)?;
+            hashes.push(hash);
         }
 
         while hashes.len() > 1 {
             let curr_hashes = hashes;
             hashes = Vec::new();
 
-            for hash_page in curr_hashes.chunks(V0HashPage::MAX_HASHES_PER_PAGE) {
-                let hash_page = Page::V0HashPage(V0HashPage {
-                    hashes: hash_page.to_vec(),
-                });
-                let mut encoded = Vec::new();
-                hash_page.bin_write(&mut encoded).unwrap();
+            for page in V0HashPage::new_pages(&curr_hashes) {
+                let hash = hash_page(Page::V0HashPage(page), &mut handle)?;
+                hashes.push(hash);
+            }
+        }
+
+        // Empty content is encoded as a single empty content page, so that it still
+        // has a root hash.
+        if hashes.is_empty() {
+            let page = V0ContentPage {
+                contents: Vec::new(),
+            };
+            hashes.push(hash_page(Page::V0ContentPage(page), &mut handle)?);
+        }
+
+        Ok(PreimageHash::from(&hashes.remove(0)))
+    }
+
+    /// Encodes `page` and passes it to `handle` along with its preimage hash.
+    fn hash_page(
+        page: Page,
+        handle: &mut impl FnMut(PreimageHash, Vec<u8>),
+    ) -> Result<[u8; PREIMAGE_HASH_SIZE], crypto::blake2b::Blake2bError> {
+        let mut encoded = Vec::new();
+        page.bin_write(&mut encoded).unwrap();
 
-                let hash = make_preimage_hash(&encoded)?.into();
-                let hash = PreimageHash { hash };
+        let hash = make_preimage_hash(&encoded)?;
+        handle(PreimageHash::from(&hash), encoded);
 
-                hashes.push(hash.clone());
+        Ok(hash)
+    }
+
+    /// Writes the preimages of `content` to `dir`, and returns the root hash.
+    ///
+    /// Each page is written to `<dir>/<hex hash>`, the layout expected in the
+    /// preimages directory of the rollup node. Empty content is written as a
+    /// single empty content page.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn write_preimages(
+        content: &[u8],
+        dir: &std::path::Path,
+    ) -> std::io::Result<PreimageHash> {
+        use std::io::{Error, ErrorKind};
 
-                handle(hash, encoded);
+        std::fs::create_dir_all(dir)?;
+
+        let mut written = Ok(());
+        let root_hash = prepare_preimages(content, |hash, page| {
+            if written.is_ok() {
+                written = std::fs::write(dir.join(hex::encode(hash.as_ref())), page);
             }
-        }
+        })
+        .map_err(|err| Error::new(ErrorKind::Other, format!("{:?}", err)))?;
+        written?;
 
-        Ok(hashes.remove(0))
+        Ok(root_hash)
     }
 }
 
@@ -380,7 +413,7 @@ mod tests {
     use tezos_data_encoding::nom::NomReader;
 
     // taken from DAC test example in tezos
-    const EXAMPLE_CONTENT_PAGE: &'static [u8] = &[
+    const EXAMPLE_CONTENT_PAGE: &[u8] = &[
         0, 0, 0, 0, b'A', b'L', b'o', b'r', b'e', b'm', b' ', b'i', b'p', b's', b'u',
         b'm', b' ', b'd', b'o', b'l', b'o', b'r', b' ', b's', b'i', b't', b' ', b'a',
         b'm', b'e', b't', b',', b' ', b'c', b'o', b'n', b's', b'e', b'c', b't', b'e',
@@ -390,12 +423,12 @@ mod tests {
     ];
 
     // taken from DAC test example in tezos
-    const EXAMPLE_HASH_PAGE: &'static [u8] = &[
-        1, 0, 0, 0, b'B', 0, b'r', 180, b'a', b'2', b'Z', b'(', 220, 014, 004, 220, b'{',
-        b'N', b'n', b'@', 183, b'#', b'!', 006, b'm', 204, b'p', 130, 162, 247, 246, 016,
-        b'l', 239, b'7', b'"', 249, 163, 0, 155, 167, 146, 019, 175, 028, b'V', 129, 247,
-        208, 031, b'F', b'd', 183, 194, 149, b'H', 163, b'|', 246, 164, 201, b'&', 195,
-        129, 024, 003, b'}', b'4', b't', 011, 213,
+    const EXAMPLE_HASH_PAGE: &[u8] = &[
+        1, 0, 0, 0, b'B', 0, b'r', 180, b'a', b'2', b'Z', b'(', 220, 14, 4, 220, b'{',
+        b'N', b'n', b'@', 183, b'#', b'!', 6, b'm', 204, b'p', 130, 162, 247, 246, 16,
+        b'l', 239, b'7', b'"', 249, 163, 0, 155, 167, 146, 19, 175, 28, b'V', 129, 247,
+        208, 31, b'F', b'd', 183, 194, 149, b'H', 163, b'|', 246, 164, 201, b'&', 195,
+        129, 24, 3, b'}', b'4', b't', 11, 213,
     ];
 
     #[test]
@@ -502,7 +535,75 @@ mod tests {
         assert_eq!(data, revealed, "Revealed different contents to original")
     }
 
-    fn save_content<Host: host::runtime::Runtime>(
+    #[test]
+    fn prepare_preimages_of_empty_content() {
+        let mut pages = Vec::new();
+        let root_hash =
+            prepare_preimages(&[], |hash, page| pages.push((hash, page))).unwrap();
+
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].0, root_hash);
+        assert_eq!(pages[0].1, [0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn write_preimages_to_dir() {
+        let data = [b'a'; 2 * V0ContentPage::MAX_CONTENT_SIZE + 1];
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .subsec_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "dac_write_preimages_{}_{}",
+            std::process::id(),
+            nonce
+        ));
+
+        let root_hash = write_preimages(&data, &dir).unwrap();
+
+        let mut host_state = mock_runtime::state::HostState::default();
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            let page = std::fs::read(&path).unwrap();
+            let hash = make_preimage_hash(&page).unwrap();
+            assert_eq!(
+                path.file_name().unwrap().to_str(),
+                Some(hex::encode(hash).as_str())
+            );
+            host_state.set_preimage(page);
+        }
+        assert!(dir.join(hex::encode(root_hash.as_ref())).exists());
+
+        let mut revealed = Vec::with_capacity(data.len());
+        let mut host = mock_runtime::host::MockHost::from(host_state);
+        let mut buffer = [0; MAX_PAGE_SIZE * 2];
+        let mut save_revealed = save_content(&mut revealed);
+        reveal_loop(
+            &mut host,
+            0,
+            root_hash.as_ref(),
+            &mut buffer,
+            2,
+            &mut save_revealed,
+        )
+        .unwrap();
+
+        drop(save_revealed);
+        assert_eq!(data.as_slice(), revealed);
+
+        // Empty content is written as a single empty content page
+        let empty_dir = dir.join("empty");
+        let root_hash = write_preimages(&[], &empty_dir).unwrap();
+        let pages: Vec<_> = std::fs::read_dir(&empty_dir).unwrap().collect();
+        assert_eq!(pages.len(), 1);
+        let root_page = empty_dir.join(hex::encode(root_hash.as_ref()));
+        let page = std::fs::read(root_page).unwrap();
+        assert_eq!(page, [0, 0, 0, 0, 0]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn save_content<Host: Runtime>(
         buffer: &mut Vec<u8>,
     ) -> impl FnMut(&mut Host, V0SliceContentPage) -> Result<(), &'static str> + '_ {
         |_, page| {
@@ -15,6 +15,8 @@ extern crate tezos_crypto_rs as crypto;
 
 #[cfg(feature = "crypto")]
 pub mod contract;
+#[cfg(feature = "dac")]
+pub mod dac;
 #[cfg(feature = "alloc")]
 pub mod entrypoint;
 #[cfg(feature = "alloc")]
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: MIT
 
 //! Definitions & tezos-encodings for *michelson* data.
+use nom::branch::alt;
 use nom::combinator::map;
 use std::fmt::Debug;
 use tezos_data_encoding::enc::{self, BinResult, BinWriter};
@@ -16,10 +17,12 @@ pub mod ticket;
 
 use super::contract::Contract;
 use micheline::{
-    bin_write_micheline_bytes, bin_write_micheline_int, bin_write_micheline_string,
+    bin_write_micheline_bytes, bin_write_micheline_int, bin_write_micheline_seq,
+    bin_write_micheline_string, bin_write_prim_1_arg_no_annots,
     bin_write_prim_2_args_no_annots, bin_write_prim_no_args_no_annots,
-    nom_read_micheline_bytes, nom_read_micheline_int, nom_read_micheline_string,
-    MichelinePrim2ArgsNoAnnots, MichelinePrimNoArgsNoAnnots,
+    nom_read_micheline_bytes, nom_read_micheline_int, nom_read_micheline_seq,
+    nom_read_micheline_string, MichelinePrim1ArgNoAnnots, MichelinePrim2ArgsNoAnnots,
+    MichelinePrimNoArgsNoAnnots,
 };
 use v1_primitives as prim;
 
@@ -28,9 +31,15 @@ pub mod v1_primitives {
     //!
     //! [michelson_v1_primitives]: <https://gitlab.com/tezos/tezos/-/blob/9028b797894a5d9db38bc61a20abb793c3778316/src/proto_alpha/lib_protocol/michelson_v1_primitives.ml>
 
+    /// `("Left", D_LEFT)` case tag.
+    pub const LEFT_TAG: u8 = 5;
+
     /// `("Pair", D_PAIR)` case tag.
     pub const PAIR_TAG: u8 = 7;
 
+    /// `("Right", D_RIGHT)` case tag.
+    pub const RIGHT_TAG: u8 = 8;
+
     /// unit encoding case tag.
     pub const UNIT_TAG: u8 = 11;
 }
@@ -51,6 +60,17 @@ where
     Arg1: Michelson,
 {
 }
+impl<Arg0, Arg1> Michelson for MichelsonOr<Arg0, Arg1>
+where
+    Arg0: Michelson,
+    Arg1: Michelson,
+{
+}
+impl<Arg> Michelson for MichelsonList<Arg>
+where
+    Arg: Michelson,
+{
+}
 
 /// Michelson *unit* encoding.
 #[derive(Debug, PartialEq, Eq)]
@@ -68,6 +88,25 @@ where
     Arg0: Debug + PartialEq + Eq,
     Arg1: Debug + PartialEq + Eq;
 
+/// Michelson *or* encoding.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MichelsonOr<Arg0, Arg1>
+where
+    Arg0: Debug + PartialEq + Eq,
+    Arg1: Debug + PartialEq + Eq,
+{
+    /// The `Left` case
+    Left(Arg0),
+    /// The `Right` case
+    Right(Arg1),
+}
+
+/// Michelson *list* encoding.
+#[derive(Debug, PartialEq, Eq)]
+pub struct MichelsonList<Arg>(pub Vec<Arg>)
+where
+    Arg: Debug + PartialEq + Eq;
+
 /// Michelson String encoding.
 #[derive(Debug, PartialEq, Eq)]
 pub struct MichelsonString(pub String);
@@ -132,6 +171,25 @@ where
     }
 }
 
+impl<Arg0, Arg1> HasEncoding for MichelsonOr<Arg0, Arg1>
+where
+    Arg0: Debug + PartialEq + Eq,
+    Arg1: Debug + PartialEq + Eq,
+{
+    fn encoding() -> Encoding {
+        Encoding::Custom
+    }
+}
+
+impl<Arg> HasEncoding for MichelsonList<Arg>
+where
+    Arg: Debug + PartialEq + Eq,
+{
+    fn encoding() -> Encoding {
+        Encoding::Custom
+    }
+}
+
 impl HasEncoding for MichelsonString {
     fn encoding() -> Encoding {
         Encoding::Custom
@@ -165,7 +223,7 @@ impl NomReader for MichelsonContract {
 impl NomReader for MichelsonUnit {
     fn nom_read(input: &[u8]) -> NomResult<Self> {
         map(
-            MichelinePrimNoArgsNoAnnots::<{ prim::PAIR_TAG }>::nom_read,
+            MichelinePrimNoArgsNoAnnots::<{ prim::UNIT_TAG }>::nom_read,
             |_prim| MichelsonUnit,
         )(input)
     }
@@ -184,6 +242,34 @@ where
     }
 }
 
+impl<Arg0, Arg1> NomReader for MichelsonOr<Arg0, Arg1>
+where
+    Arg0: NomReader + Debug + PartialEq + Eq,
+    Arg1: NomReader + Debug + PartialEq + Eq,
+{
+    fn nom_read(input: &[u8]) -> NomResult<Self> {
+        alt((
+            map(
+                MichelinePrim1ArgNoAnnots::<_, { prim::LEFT_TAG }>::nom_read,
+                |micheline| MichelsonOr::Left(micheline.arg),
+            ),
+            map(
+                MichelinePrim1ArgNoAnnots::<_, { prim::RIGHT_TAG }>::nom_read,
+                |micheline| MichelsonOr::Right(micheline.arg),
+            ),
+        ))(input)
+    }
+}
+
+impl<Arg> NomReader for MichelsonList<Arg>
+where
+    Arg: NomReader + Debug + PartialEq + Eq,
+{
+    fn nom_read(input: &[u8]) -> NomResult<Self> {
+        map(nom_read_micheline_seq, MichelsonList)(input)
+    }
+}
+
 impl NomReader for MichelsonString {
     fn nom_read(input: &[u8]) -> NomResult<Self> {
         map(nom_read_micheline_string, MichelsonString)(input)
@@ -256,6 +342,32 @@ where
     }
 }
 
+impl<Arg0, Arg1> BinWriter for MichelsonOr<Arg0, Arg1>
+where
+    Arg0: BinWriter + Debug + PartialEq + Eq,
+    Arg1: BinWriter + Debug + PartialEq + Eq,
+{
+    fn bin_write(&self, output: &mut Vec<u8>) -> BinResult {
+        match self {
+            MichelsonOr::Left(left) => {
+                bin_write_prim_1_arg_no_annots::<_, { prim::LEFT_TAG }>(left, output)
+            }
+            MichelsonOr::Right(right) => {
+                bin_write_prim_1_arg_no_annots::<_, { prim::RIGHT_TAG }>(right, output)
+            }
+        }
+    }
+}
+
+impl<Arg> BinWriter for MichelsonList<Arg>
+where
+    Arg: BinWriter + Debug + PartialEq + Eq,
+{
+    fn bin_write(&self, output: &mut Vec<u8>) -> BinResult {
+        bin_write_micheline_seq(self.0.as_slice(), output)
+    }
+}
+
 impl BinWriter for MichelsonString {
     fn bin_write(&self, output: &mut Vec<u8>) -> BinResult {
         bin_write_micheline_string(&self.0, output)
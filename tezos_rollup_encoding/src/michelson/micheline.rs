@@ -10,6 +10,8 @@
 //! [lib_micheline]: <https://gitlab.com/tezos/tezos/-/blob/9028b797894a5d9db38bc61a20abb793c3778316/src/lib_micheline/micheline_encoding.ml>
 use nom::bytes::complete::tag;
 use nom::combinator::map;
+use nom::multi::length_data;
+use nom::number::complete::be_u32;
 use nom::sequence::{pair, preceded};
 use tezos_data_encoding::enc::{self, BinResult, BinSerializer, BinWriter};
 use tezos_data_encoding::encoding::{Encoding, HasEncoding};
@@ -23,8 +25,12 @@ use std::fmt::Debug;
 pub const MICHELINE_INT_TAG: u8 = 0;
 /// String encoding case tag.
 pub const MICHELINE_STRING_TAG: u8 = 1;
+/// Sequence encoding case tag.
+pub const MICHELINE_SEQ_TAG: u8 = 2;
 /// no-argument primitive (without annotations) encoding case tag.
 pub const MICHELINE_PRIM_NO_ARGS_NO_ANNOTS_TAG: u8 = 3;
+/// 1-argument primitive (without annotations) encoding case tag.
+pub const MICHELINE_PRIM_1_ARG_NO_ANNOTS_TAG: u8 = 5;
 /// 2-argument primitive (without annotations) encoding case tag.
 pub const MICHELINE_PRIM_2_ARGS_NO_ANNOTS_TAG: u8 = 7;
 /// Bytes encoding case tag.
@@ -59,6 +65,19 @@ pub struct MichelineBytes(pub Vec<u8>);
 #[derive(Debug, PartialEq, Eq)]
 pub struct MichelinePrimNoArgsNoAnnots<const PRIM_TAG: u8>;
 
+/// lib_micheline *prim-1 no annotations* encoding.
+///
+/// Encoded as an `obj2`, prefixed by [MICHELINE_PRIM_1_ARG_NO_ANNOTS_TAG], with fields:
+/// - `prim` - the `PRIM_TAG`
+/// - `arg` - the argument
+#[derive(Debug, PartialEq, Eq)]
+pub struct MichelinePrim1ArgNoAnnots<Arg, const PRIM_TAG: u8>
+where
+    Arg: Debug + PartialEq + Eq,
+{
+    pub(crate) arg: Arg,
+}
+
 /// lib_micheline *prim-2 no annotations* encoding.
 ///
 /// Encoded as an `obj3`, prefixed by [MICHELINE_PRIM_2_ARGS_NO_ANNOTS_TAG], with fields:
@@ -95,6 +114,15 @@ has_encoding!(MichelineBytes, MICHELINE_BYTES_ENCODING, {
     Encoding::Custom
 });
 
+impl<Arg, const PRIM_TAG: u8> HasEncoding for MichelinePrim1ArgNoAnnots<Arg, PRIM_TAG>
+where
+    Arg: Debug + PartialEq + Eq,
+{
+    fn encoding() -> Encoding {
+        Encoding::Custom
+    }
+}
+
 impl<Arg1, Arg2, const PRIM_TAG: u8> HasEncoding
     for MichelinePrim2ArgsNoAnnots<Arg1, Arg2, PRIM_TAG>
 where
@@ -133,6 +161,20 @@ impl NomReader for MichelineBytes {
     }
 }
 
+impl<Arg, const PRIM_TAG: u8> NomReader for MichelinePrim1ArgNoAnnots<Arg, PRIM_TAG>
+where
+    Arg: NomReader + Debug + PartialEq + Eq,
+{
+    fn nom_read(input: &[u8]) -> NomResult<Self> {
+        let parse = preceded(
+            tag([MICHELINE_PRIM_1_ARG_NO_ANNOTS_TAG, PRIM_TAG]),
+            Arg::nom_read,
+        );
+
+        map(parse, |arg| MichelinePrim1ArgNoAnnots { arg })(input)
+    }
+}
+
 impl<Arg1, Arg2, const PRIM_TAG: u8> NomReader
     for MichelinePrim2ArgsNoAnnots<Arg1, Arg2, PRIM_TAG>
 where
@@ -184,6 +226,15 @@ impl BinWriter for MichelineBytes {
     }
 }
 
+impl<Arg, const PRIM_TAG: u8> BinWriter for MichelinePrim1ArgNoAnnots<Arg, PRIM_TAG>
+where
+    Arg: BinWriter + Debug + PartialEq + Eq,
+{
+    fn bin_write(&self, output: &mut Vec<u8>) -> BinResult {
+        bin_write_prim_1_arg_no_annots::<_, { PRIM_TAG }>(&self.arg, output)
+    }
+}
+
 impl<Arg1, Arg2, const PRIM_TAG: u8> BinWriter
     for MichelinePrim2ArgsNoAnnots<Arg1, Arg2, PRIM_TAG>
 where
@@ -231,9 +282,39 @@ pub(crate) fn nom_read_micheline_int(input: NomInput) -> NomResult<Zarith> {
     nom_read_tagged_micheline::<_, { MICHELINE_INT_TAG }>(Zarith::nom_read)(input)
 }
 
+/// Read a dynamically-sized sequence with a prefix of [MICHELINE_SEQ_TAG].
+///
+/// The elements are read with `T::nom_read` until the sequence is exhausted.
+pub(crate) fn nom_read_micheline_seq<T: NomReader>(input: NomInput) -> NomResult<Vec<T>> {
+    let (remaining, mut elements) =
+        preceded(tag([MICHELINE_SEQ_TAG]), length_data(be_u32))(input)?;
+
+    let mut seq = Vec::new();
+    while !elements.is_empty() {
+        let (rest, element) = T::nom_read(elements)?;
+        seq.push(element);
+        elements = rest;
+    }
+
+    Ok((remaining, seq))
+}
+
 // -------------------------
 // Serialization Combinators
 // -------------------------
+/// Write `PRIM_TAG` & `arg` into an `obj2` encoding, prefixed with the
+/// [MICHELINE_PRIM_1_ARG_NO_ANNOTS_TAG].
+pub(crate) fn bin_write_prim_1_arg_no_annots<Arg, const PRIM_TAG: u8>(
+    arg: &Arg,
+    output: &mut Vec<u8>,
+) -> BinResult
+where
+    Arg: BinWriter,
+{
+    enc::put_bytes(&[MICHELINE_PRIM_1_ARG_NO_ANNOTS_TAG, PRIM_TAG], output);
+    arg.bin_write(output)
+}
+
 /// Write `PRIM_TAG`, `arg1` & `arg2` into an `obj3` encoding, prefixed with the
 /// [MICHELINE_PRIM_2_ARGS_NO_ANNOTS_TAG].
 pub(crate) fn bin_write_prim_2_args_no_annots<Arg1, Arg2, const PRIM_TAG: u8>(
@@ -293,6 +374,27 @@ pub(crate) fn bin_write_micheline_string(
     bin_write_tagged_micheline::<_, { MICHELINE_STRING_TAG }>(enc::string, data, output)
 }
 
+/// Write the elements into a dynamically-sized sequence, prefixed by
+/// [MICHELINE_SEQ_TAG].
+pub(crate) fn bin_write_micheline_seq<T: BinWriter>(
+    data: &[T],
+    output: &mut Vec<u8>,
+) -> BinResult {
+    bin_write_tagged_micheline::<_, { MICHELINE_SEQ_TAG }>(
+        enc::dynamic(bin_write_elements::<T>),
+        data,
+        output,
+    )
+}
+
+/// Write the elements one after the other.
+fn bin_write_elements<T: BinWriter>(data: &[T], output: &mut Vec<u8>) -> BinResult {
+    for element in data {
+        element.bin_write(output)?;
+    }
+    Ok(())
+}
+
 /// Write the zarith into an int, prefixed by [MICHELINE_INT_TAG].
 pub(crate) fn bin_write_micheline_int(data: &Zarith, output: &mut Vec<u8>) -> BinResult {
     bin_write_tagged_micheline::<_, { MICHELINE_INT_TAG }>(
@@ -463,6 +565,75 @@ mod test {
         assert_eq!(expected, bin);
     }
 
+    #[test]
+    fn micheline_prim_1_arg_decode() {
+        let test = vec![
+            5, // Prim_1
+            8, // Prim tag
+            0, // int encoding tag
+            3, // amount
+        ];
+
+        let expected = MichelinePrim1ArgNoAnnots::<MichelineInt, 8> { arg: 3.into() };
+
+        let (remaining_input, prim) = NomReader::nom_read(test.as_slice()).unwrap();
+
+        assert!(remaining_input.is_empty());
+        assert_eq!(expected, prim);
+    }
+
+    #[test]
+    fn micheline_prim_1_arg_encode() {
+        let expected = vec![
+            5, // Prim_1
+            5, // Prim tag
+            1, // String tag
+            0, 0, 0, 4, // String size
+            b'l', b'e', b'f', b't', // string contents
+        ];
+
+        let test = MichelinePrim1ArgNoAnnots::<_, 5> {
+            arg: MichelineString("left".into()),
+        };
+
+        let mut bin = Vec::new();
+        test.bin_write(&mut bin).unwrap();
+
+        assert_eq!(expected, bin);
+    }
+
+    #[test]
+    fn micheline_seq_encode() {
+        let test = [MichelineInt::from(1), MichelineInt::from(2)];
+        let expected = vec![
+            2, // MICHELINE_SEQ_TAG
+            0, 0, 0, 4, // SIZE
+            0, 1, // first int
+            0, 2, // second int
+        ];
+
+        let mut bin = Vec::new();
+        bin_write_micheline_seq(&test, &mut bin).unwrap();
+
+        assert_eq!(expected, bin);
+    }
+
+    #[test]
+    fn micheline_seq_decode() {
+        let test = vec![
+            2, // MICHELINE_SEQ_TAG
+            0, 0, 0, 4, // SIZE
+            0, 1, // first int
+            0, 2, // second int
+        ];
+
+        let (remaining_input, seq) =
+            nom_read_micheline_seq::<MichelineInt>(test.as_slice()).unwrap();
+
+        assert!(remaining_input.is_empty());
+        assert_eq!(vec![MichelineInt::from(1), MichelineInt::from(2)], seq);
+    }
+
     fn hex_to_bigint(s: &str) -> num_bigint::BigInt {
         use num_traits::FromPrimitive;
         num_bigint::BigInt::from_u64(u64::from_str_radix(s, 16).unwrap()).unwrap()
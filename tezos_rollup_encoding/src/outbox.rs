@@ -89,7 +89,8 @@ pub struct OutboxMessageTransaction<Expr: Michelson> {
 
 #[cfg(test)]
 mod test {
-    use crate::michelson::ticket::StringTicket;
+    use crate::michelson::ticket::{StringTicket, Ticket, UnitTicket};
+    use crate::michelson::MichelsonUnit;
 
     use super::*;
 
@@ -198,6 +199,27 @@ mod test {
         ));
     }
 
+    #[test]
+    fn encode_decode_unit_ticket_transaction() {
+        let ticket = UnitTicket::new(
+            Contract::from_b58check("KT1ThEdxfUcWUwqsdergy3QnbCWGHSUHeHJq").unwrap(),
+            MichelsonUnit,
+            10 as u64,
+        )
+        .unwrap();
+        let transaction =
+            make_transaction(ticket, "KT1BuEZtb68c1Q4yjtckcNjGELqWt56Xyesc", "burn");
+
+        let mut bin = vec![];
+        transaction.bin_write(&mut bin).unwrap();
+
+        let (remaining, decoded) =
+            OutboxMessageTransaction::<UnitTicket>::nom_read(bin.as_slice()).unwrap();
+
+        assert!(remaining.is_empty());
+        assert_eq!(transaction, decoded);
+    }
+
     fn transaction_one() -> OutboxMessageTransaction<StringTicket> {
         let ticket = StringTicket::new(
             Contract::from_b58check("KT1ThEdxfUcWUwqsdergy3QnbCWGHSUHeHJq").unwrap(),
@@ -218,11 +240,11 @@ mod test {
         make_transaction(ticket, "KT1AaiUqbT3NmQts2w7ofY4vJviVchztiW4y", "another")
     }
 
-    fn make_transaction(
-        ticket: StringTicket,
+    fn make_transaction<Expr: Michelson>(
+        ticket: Ticket<Expr>,
         destination: &str,
         entrypoint: &str,
-    ) -> OutboxMessageTransaction<StringTicket> {
+    ) -> OutboxMessageTransaction<Ticket<Expr>> {
         let parameters = ticket;
         let destination = Contract::from_b58check(destination).unwrap();
         let entrypoint = Entrypoint::try_from(entrypoint.to_string()).unwrap();